@@ -0,0 +1,409 @@
+use std::{cmp::Ordering, mem};
+
+use crate::MinSmallVec;
+
+/// A tree of [MinSmallVec] nodes with a known minimum value, which stays balanced like a B-tree.
+///
+/// The elements are stored in order in the leaves, and every inner node is a [MinSmallVec]
+/// of its children, whose minimum is the child with the smallest min value.
+/// Nodes are split when they exceed their inline capacity `B` and merged with a sibling when
+/// they have less than `B / 2` entries, so all leaves have the same depth,
+/// which grows logarithmically with the number of elements.
+///
+/// Modifying, inserting or removing an element updates the min value of each node on the path
+/// to its leaf like [MinSmallVec::modify_single], instead of scanning all elements.
+/// Comparing two nodes follows their min values down to the leaves.
+///
+/// Unlike [MinTree](crate::MinTree), elements are addressed by their position in the whole tree.
+///
+/// ```rust
+/// # use min_smallvec::MinBTree;
+/// let mut tree = MinBTree::<u32, 4>::new();
+/// tree.extend(0..100);
+/// assert!(tree.depth() > 1);
+/// tree.modify(0, |val| *val = 50);
+/// assert_eq!(tree.get_min(), Some(&1));
+/// assert_eq!(tree.pop_min(), Some(1));
+/// assert_eq!(tree.get_min_index(), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinBTree<T, const B: usize> {
+    root: Node<T, B>,
+}
+
+#[derive(Debug, Clone)]
+enum Node<T, const B: usize> {
+    Leaf(MinSmallVec<T, B>),
+    Inner {
+        /// Children of the same depth, which are never empty
+        children: MinSmallVec<Box<Node<T, B>>, B>,
+        /// Number of elements in all children
+        len: usize,
+    },
+}
+
+impl<T: PartialOrd, const B: usize> MinBTree<T, B> {
+    /// Creates an empty tree.
+    ///
+    /// Panics if `B < 4`, as nodes with a single child could not be merged otherwise.
+    pub fn new() -> Self {
+        assert!(B >= 4, "nodes need a capacity of at least 4");
+
+        Self {
+            root: Node::Leaf(MinSmallVec::new()),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of levels of nodes, which is `1` if the root is a leaf.
+    pub fn depth(&self) -> usize {
+        let mut depth = 1;
+        let mut node = &self.root;
+
+        while let Node::Inner { children, .. } = node {
+            node = &children[0];
+            depth += 1;
+        }
+
+        depth
+    }
+
+    /// Returns `true` if any node has spilled to the heap.
+    #[cfg(test)]
+    pub(crate) fn spilled(&self) -> bool {
+        self.root.spilled()
+    }
+
+    /// Returns an iterator over references to all elements.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.root.iter()
+    }
+
+    /// Get a reference to the element at `index`, or [None] if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        (index < self.len()).then(|| self.root.get(index))
+    }
+
+    /// Get a reference to the minimum value.
+    pub fn get_min(&self) -> Option<&T> {
+        self.root.min()
+    }
+
+    /// Get the index of the minimum value.
+    pub fn get_min_index(&self) -> Option<usize> {
+        self.root.min_index()
+    }
+
+    /// Modifies the element at `index`, updating the min value of every node on the path to it.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn modify(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        assert!(index < self.len(), "index out of bounds");
+        self.root.modify(index, func);
+    }
+
+    /// Pushes a value after the last element.
+    pub fn push(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    /// Inserts a value at `index`, shifting all elements after it to the right.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "insertion index out of bounds");
+
+        if let Some(right) = self.root.insert(index, value) {
+            let left = mem::replace(&mut self.root, Node::Leaf(MinSmallVec::new()));
+            let len = left.len() + right.len();
+            self.root = Node::Inner {
+                children: [Box::new(left), right].into_iter().collect(),
+                len,
+            };
+        }
+    }
+
+    /// Removes the last element and returns it, or [None] if the tree is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.len().checked_sub(1)?;
+        Some(self.remove(index))
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "index out of bounds");
+        let value = self.root.remove(index);
+
+        // an inner root with a single child is replaced by it
+        while let Node::Inner { children, .. } = &mut self.root {
+            if children.len() > 1 {
+                break;
+            }
+            self.root = *children.pop().expect("inner nodes are never empty");
+        }
+
+        value
+    }
+
+    /// Removes and returns the minimum value, preserving the order of the remaining elements.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.get_min_index().map(|index| self.remove(index))
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.root = Node::Leaf(MinSmallVec::new());
+    }
+}
+
+impl<T: PartialOrd, const B: usize> Node<T, B> {
+    /// Minimum number of entries of a node other than the root
+    const MIN_WIDTH: usize = B / 2;
+
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(values) => values.len(),
+            Node::Inner { len, .. } => *len,
+        }
+    }
+
+    /// Returns the number of values or children.
+    fn width(&self) -> usize {
+        match self {
+            Node::Leaf(values) => values.len(),
+            Node::Inner { children, .. } => children.len(),
+        }
+    }
+
+    #[cfg(test)]
+    fn spilled(&self) -> bool {
+        match self {
+            Node::Leaf(values) => values.spilled(),
+            Node::Inner { children, .. } => {
+                children.spilled() || children.iter().any(|child| child.spilled())
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self {
+            Node::Leaf(values) => Box::new(values.iter()),
+            Node::Inner { children, .. } => {
+                Box::new(children.iter().flat_map(|child| child.iter()))
+            }
+        }
+    }
+
+    /// Returns the child containing the element at `index` and the index within it.
+    ///
+    /// An `index` equal to the length of a child refers to it only if `inclusive` is set,
+    /// to insert an element after its last one.
+    fn find(children: &[Box<Self>], mut index: usize, inclusive: bool) -> (usize, usize) {
+        for (i, child) in children.iter().enumerate() {
+            if index < child.len() || (inclusive && index == child.len()) {
+                return (i, index);
+            }
+            index -= child.len();
+        }
+
+        unreachable!("index out of bounds")
+    }
+
+    fn get(&self, index: usize) -> &T {
+        match self {
+            Node::Leaf(values) => &values[index],
+            Node::Inner { children, .. } => {
+                let (i, index) = Self::find(children, index, false);
+                children[i].get(index)
+            }
+        }
+    }
+
+    fn min(&self) -> Option<&T> {
+        match self {
+            Node::Leaf(values) => values.get_min(),
+            Node::Inner { children, .. } => children.get_min()?.min(),
+        }
+    }
+
+    fn min_index(&self) -> Option<usize> {
+        match self {
+            Node::Leaf(values) => values.get_min_index(),
+            Node::Inner { children, .. } => {
+                let (i, child) = children.get_min_entry()?;
+                let offset: usize = children[..i].iter().map(|child| child.len()).sum();
+                Some(offset + child.min_index()?)
+            }
+        }
+    }
+
+    fn modify(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        match self {
+            Node::Leaf(values) => values.modify_single(index, func),
+            Node::Inner { children, .. } => {
+                let (i, index) = Self::find(children, index, false);
+                // the guard updates the min value of this node when dropped
+                let mut child = children.get_mut(i).expect("child index is in bounds");
+                child.modify(index, func);
+            }
+        }
+    }
+
+    /// Inserts `value` at `index` and returns the upper half of this node if it has been split.
+    fn insert(&mut self, index: usize, value: T) -> Option<Box<Self>> {
+        match self {
+            Node::Leaf(values) => values.insert(index, value),
+            Node::Inner { children, len } => {
+                *len += 1;
+                let (i, index) = Self::find(children, index, true);
+                let mut child = children.get_mut(i).expect("child index is in bounds");
+                let split = child.insert(index, value);
+                drop(child);
+
+                if let Some(node) = split {
+                    children.insert(i + 1, node);
+                }
+            }
+        }
+
+        (self.width() > B).then(|| self.split())
+    }
+
+    /// Removes and returns the element at `index`, merging the child it was removed from
+    /// with a sibling if it has become too small.
+    fn remove(&mut self, index: usize) -> T {
+        match self {
+            Node::Leaf(values) => values.remove(index),
+            Node::Inner { children, len } => {
+                *len -= 1;
+                let (i, index) = Self::find(children, index, false);
+                let mut child = children.get_mut(i).expect("child index is in bounds");
+                let value = child.remove(index);
+                let width = child.width();
+                drop(child);
+
+                if width < Self::MIN_WIDTH && children.len() > 1 {
+                    Self::rebalance(children, i);
+                }
+                value
+            }
+        }
+    }
+
+    /// Merges the child at `index` with a neighbor, splitting the result again if it is too large.
+    fn rebalance(children: &mut MinSmallVec<Box<Self>, B>, index: usize) {
+        let left = index.saturating_sub(1);
+        let right = children.remove(left + 1);
+
+        let mut node = children.get_mut(left).expect("child index is in bounds");
+        node.append(*right);
+        let split = (node.width() > B).then(|| node.split());
+        drop(node);
+
+        if let Some(node) = split {
+            children.insert(left + 1, node);
+        }
+    }
+
+    /// Moves the upper half of the entries into a new node of the same depth.
+    ///
+    /// The entries of an overfull node have spilled to the heap,
+    /// so the remaining ones are moved back inline.
+    fn split(&mut self) -> Box<Self> {
+        let mid = self.width() / 2;
+
+        Box::new(match self {
+            Node::Leaf(values) => {
+                let upper = values.drain(mid..).collect();
+                values.shrink_to_fit();
+                Node::Leaf(upper)
+            }
+            Node::Inner { children, len } => {
+                let upper: MinSmallVec<_, B> = children.drain(mid..).collect();
+                children.shrink_to_fit();
+                let moved = upper.iter().map(|child| child.len()).sum::<usize>();
+                *len -= moved;
+                Node::Inner {
+                    children: upper,
+                    len: moved,
+                }
+            }
+        })
+    }
+
+    /// Appends the entries of `other`, which has the same depth.
+    fn append(&mut self, other: Self) {
+        match (self, other) {
+            (Node::Leaf(values), Node::Leaf(other)) => values.extend(other),
+            (
+                Node::Inner { children, len },
+                Node::Inner {
+                    children: other,
+                    len: other_len,
+                },
+            ) => {
+                children.extend(other);
+                *len += other_len;
+            }
+            _ => unreachable!("siblings have the same depth"),
+        }
+    }
+}
+
+/// Compares the min values of two nodes of the same depth, consistent with [PartialOrd].
+impl<T: PartialOrd, const B: usize> PartialEq for Node<T, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Compares the min values of two nodes of the same depth like [MinSmallVec] does.
+impl<T: PartialOrd, const B: usize> PartialOrd for Node<T, B> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Node::Leaf(values), Node::Leaf(other)) => values.partial_cmp(other),
+            (
+                Node::Inner { children, .. },
+                Node::Inner {
+                    children: other, ..
+                },
+            ) => children.partial_cmp(other),
+            _ => None,
+        }
+    }
+}
+
+impl<T: PartialOrd, const B: usize> Default for MinBTree<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd, const B: usize> Extend<T> for MinBTree<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: PartialOrd, const B: usize> FromIterator<T> for MinBTree<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    ops::{Deref, Index},
+    slice::SliceIndex,
+};
+
+use smallvec::SmallVec;
+
+use crate::{IncomparablePolicy, MinSmallVec, MinSmallVecError, MinState, TiePolicy};
+
+/// A collection with a known minimum value, which is determined by a key extracted from each element.
+///
+/// The key of every element is computed once, when the element is inserted or modified,
+/// and stored alongside it. All comparisons use the stored keys,
+/// which makes this cheaper than a [MinSmallVec] with a comparator if computing the key is expensive.
+///
+/// ```rust
+/// # use min_smallvec::MinByKeySmallVec;
+/// let mut vec = MinByKeySmallVec::<&str, 4, usize, _>::new(|s: &&str| s.len());
+/// vec.extend(["apple", "fig", "banana"]);
+/// assert_eq!(vec.get_min(), Some(&"fig"));
+/// assert_eq!(vec.get_min_key(), Some(&3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinByKeySmallVec<T, const S: usize, K, F> {
+    values: SmallVec<[T; S]>,
+    /// Keys of the values at the same indices, which track the min value
+    keys: MinSmallVec<K, S>,
+    key_fn: F,
+}
+
+impl<T, const S: usize, K: PartialOrd, F: Fn(&T) -> K> MinByKeySmallVec<T, S, K, F> {
+    /// Creates an empty collection whose minimum is determined by the keys returned by `key_fn`.
+    pub fn new(key_fn: F) -> Self {
+        Self {
+            values: SmallVec::new(),
+            keys: MinSmallVec::new(),
+            key_fn,
+        }
+    }
+
+    /// Creates an empty collection treating incomparable keys according to `policy`.
+    pub fn with_policy(key_fn: F, policy: IncomparablePolicy) -> Self {
+        Self {
+            values: SmallVec::new(),
+            keys: MinSmallVec::with_policy(policy),
+            key_fn,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a slice of all elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns a slice of the cached keys of all elements.
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Get a reference to the minimum value.
+    pub fn get_min(&self) -> Option<&T> {
+        self.keys.get_min_index().map(|index| &self.values[index])
+    }
+
+    /// Get a reference to the cached key of the minimum value.
+    pub fn get_min_key(&self) -> Option<&K> {
+        self.keys.get_min()
+    }
+
+    /// Get the index of the minimum value.
+    pub fn get_min_index(&self) -> Option<usize> {
+        self.keys.get_min_index()
+    }
+
+    /// Get the index of the minimum value together with a reference to it.
+    pub fn get_min_entry(&self) -> Option<(usize, &T)> {
+        self.keys
+            .get_min_index()
+            .map(|index| (index, &self.values[index]))
+    }
+
+    /// Get the state of the minimum value.
+    pub fn min_state(&self) -> MinState {
+        self.keys.min_state()
+    }
+
+    /// Get the policy for incomparable keys.
+    pub fn policy(&self) -> IncomparablePolicy {
+        self.keys.policy()
+    }
+
+    /// Sets the policy for incomparable keys and recalculates the min value using a linear scan.
+    pub fn set_policy(&mut self, policy: IncomparablePolicy) {
+        self.keys.set_policy(policy);
+    }
+
+    /// Get the policy deciding which of several elements with equal keys is the min value.
+    pub fn tie_policy(&self) -> TiePolicy {
+        self.keys.tie_policy()
+    }
+
+    /// Sets the policy deciding which of several elements with equal keys is the min value
+    /// and recalculates the min value using a linear scan.
+    pub fn set_tie_policy(&mut self, tie: TiePolicy) {
+        self.keys.set_tie_policy(tie);
+    }
+
+    /// Returns `true` if the runner-up is tracked.
+    pub fn runner_up_tracking(&self) -> bool {
+        self.keys.runner_up_tracking()
+    }
+
+    /// Enables or disables tracking the runner-up like [MinSmallVec::set_runner_up_tracking].
+    pub fn set_runner_up_tracking(&mut self, enabled: bool) {
+        self.keys.set_runner_up_tracking(enabled);
+    }
+
+    /// Applies a modification function to the elements, recomputes all keys
+    /// and recalculates the min value using a linear scan.
+    pub fn modify(&mut self, mut func: impl FnMut(&mut SmallVec<[T; S]>)) {
+        func(&mut self.values);
+        let (values, key_fn) = (&self.values, &self.key_fn);
+        self.keys.modify(|keys| {
+            keys.clear();
+            keys.extend(values.iter().map(key_fn));
+        });
+    }
+
+    /// Modifies a single element and recomputes only its key.
+    /// The min value is updated the same way as [MinSmallVec::modify_single].
+    ///
+    /// Panics if the collection is empty or if `index` is out of bounds.
+    /// See [MinByKeySmallVec::try_modify_single] for a non-panicking version.
+    pub fn modify_single(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        if let Err(err) = self.try_modify_single(index, func) {
+            panic!("{err}");
+        }
+    }
+
+    /// Modifies a single element like [MinByKeySmallVec::modify_single],
+    /// but returns an error instead of panicking. `func` is not called if an error is returned.
+    pub fn try_modify_single(
+        &mut self,
+        index: usize,
+        mut func: impl FnMut(&mut T),
+    ) -> Result<(), MinSmallVecError> {
+        let (values, key_fn) = (&mut self.values, &self.key_fn);
+        self.keys.try_modify_single(index, |key| {
+            func(&mut values[index]);
+            *key = key_fn(&values[index]);
+        })
+    }
+
+    /// Pushes a value, computing its key. Only the new key is compared with the minimum.
+    pub fn push(&mut self, value: T) {
+        self.keys.push((self.key_fn)(&value));
+        self.values.push(value);
+    }
+
+    /// Inserts a value at `index`, computing its key. Only the new key is compared with the minimum.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.keys.insert(index, (self.key_fn)(&value));
+        self.values.insert(index, value);
+    }
+
+    /// Removes the last element and returns it, or [None] if the collection is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.keys.pop()?;
+        self.values.pop()
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.keys.remove(index);
+        self.values.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.keys.swap_remove(index);
+        self.values.swap_remove(index)
+    }
+
+    /// Removes and returns the minimum value, preserving the order of the remaining elements.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.keys.get_min_index().map(|index| self.remove(index))
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+    }
+}
+
+impl<T, const S: usize, K, F> Deref for MinByKeySmallVec<T, S, K, F> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const S: usize, K, F> Index<I> for MinByKeySmallVec<T, S, K, F> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.values[index]
+    }
+}
+
+impl<T, const S: usize, K: PartialOrd, F: Fn(&T) -> K> Extend<T> for MinByKeySmallVec<T, S, K, F> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let len = self.values.len();
+        self.values.extend(iter);
+        self.keys
+            .extend(self.values[len..].iter().map(&self.key_fn));
+    }
+}
+
+impl<T, const S: usize, K, F> IntoIterator for MinByKeySmallVec<T, S, K, F> {
+    type Item = T;
+    type IntoIter = smallvec::IntoIter<[T; S]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl<'a, T, const S: usize, K, F> IntoIterator for &'a MinByKeySmallVec<T, S, K, F> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+
+/// A comparison function used to find the min value of a [MinSmallVec](crate::MinSmallVec).
+///
+/// Implemented by [Natural], which uses [PartialOrd], and by closures
+/// `Fn(&T, &T) -> Ordering`, which define a total order.
+pub trait Compare<T: ?Sized> {
+    /// Compares `a` with `b`, returning [None] if they can not be compared.
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering>;
+}
+
+/// A [Compare] implementation that defines a total order, so any two elements can be compared.
+///
+/// [Compare::compare] must return `Some(self.total_compare(a, b))`.
+/// With a total order the min value is never
+/// [MinState::Incomparable](crate::MinState::Incomparable),
+/// so it is only missing if the collection is empty.
+/// [TotalCompare::total_compare] is used for sorting and by the [Ord] implementation,
+/// while the min value is still maintained using [Compare::compare].
+///
+/// Implemented by [Natural] for types implementing [Ord], by closures `Fn(&T, &T) -> Ordering`
+/// and by [TotalF64] and [TotalF32] for floats.
+pub trait TotalCompare<T: ?Sized>: Compare<T> {
+    /// Compares `a` with `b`.
+    fn total_compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+/// Compares elements using their [PartialOrd] implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Natural;
+
+impl<T: PartialOrd + ?Sized> Compare<T> for Natural {
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        a.partial_cmp(b)
+    }
+}
+
+impl<T: Ord + ?Sized> TotalCompare<T> for Natural {
+    fn total_compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        Some(self(a, b))
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> TotalCompare<T> for F {
+    fn total_compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// Reverses the order of the comparator `C`, so that the minimum becomes the maximum.
+///
+/// The [IncomparablePolicy] applies to the reversed order, so with [IncomparablePolicy::Greatest]
+/// incomparable elements are smaller than all comparable ones in the order of `C` and are never
+/// the maximum. [MinMaxSmallVec](crate::MinMaxSmallVec) tracks its maximum the same way.
+///
+/// Used by [MaxSmallVec](crate::MaxSmallVec).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Reversed<C = Natural>(pub C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for Reversed<C> {
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        self.0.compare(a, b).map(Ordering::reverse)
+    }
+}
+
+impl<T: ?Sized, C: TotalCompare<T>> TotalCompare<T> for Reversed<C> {
+    fn total_compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.total_compare(a, b).reverse()
+    }
+}
+
+/// Compares [f64]s using [f64::total_cmp], so that every value including NaN has a defined order.
+///
+/// Positive NaNs are greater than all other values and negative NaNs are smaller than all other values.
+/// `-0.0` is smaller than `0.0`.
+///
+/// ```rust
+/// # use min_smallvec::{MinSmallVec, TotalF64};
+/// let mut vec = MinSmallVec::<f64, 4, TotalF64>::default();
+/// vec.extend([2.0, f64::NAN, 1.0]);
+/// assert_eq!(vec.get_min(), Some(&1.0));
+/// vec.push(-f64::NAN);
+/// assert!(vec.get_min().unwrap().is_nan());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TotalF64;
+
+impl Compare<f64> for TotalF64 {
+    fn compare(&self, a: &f64, b: &f64) -> Option<Ordering> {
+        Some(a.total_cmp(b))
+    }
+}
+
+impl TotalCompare<f64> for TotalF64 {
+    fn total_compare(&self, a: &f64, b: &f64) -> Ordering {
+        a.total_cmp(b)
+    }
+}
+
+/// Compares [f32]s using [f32::total_cmp], so that every value including NaN has a defined order.
+///
+/// See [TotalF64] for the order of special values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TotalF32;
+
+impl Compare<f32> for TotalF32 {
+    fn compare(&self, a: &f32, b: &f32) -> Option<Ordering> {
+        Some(a.total_cmp(b))
+    }
+}
+
+impl TotalCompare<f32> for TotalF32 {
+    fn total_compare(&self, a: &f32, b: &f32) -> Ordering {
+        a.total_cmp(b)
+    }
+}
+
+/// How elements for which [Compare::compare] returns [None] are treated
+/// when calculating the min value of a [MinSmallVec](crate::MinSmallVec).
+///
+/// An element is considered incomparable if it can not be compared with itself, like [f64::NAN].
+/// With every policy except [IncomparablePolicy::Poison], two elements that can not be compared
+/// with each other but are both comparable with themselves are treated as equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IncomparablePolicy {
+    /// A single failed comparison makes the min value
+    /// [MinState::Incomparable](crate::MinState::Incomparable).
+    #[default]
+    Poison,
+    /// Incomparable elements are ignored and the min value is tracked over the comparable ones.
+    /// The min value is [MinState::Incomparable](crate::MinState::Incomparable)
+    /// if no element is comparable.
+    Skip,
+    /// Incomparable elements are greater than all comparable ones.
+    Greatest,
+    /// Incomparable elements are smaller than all comparable ones.
+    Least,
+}
+
+/// Which of several elements that compare equal to each other is the min value
+/// of a [MinSmallVec](crate::MinSmallVec).
+///
+/// The policy is respected by every operation, so the min value does not depend on the order
+/// in which the elements were inserted or modified. To break ties by the values themselves,
+/// for example by a sequence number, include them in the comparator instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TiePolicy {
+    /// The element with the smallest index is the min value.
+    #[default]
+    First,
+    /// The element with the largest index is the min value.
+    Last,
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// An error returned by the fallible methods of [MinSmallVec](crate::MinSmallVec).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MinSmallVecError {
+    /// The collection is empty, so there is no minimum value.
+    Empty,
+    /// The collection has no minimum value because [Compare::compare](crate::Compare::compare)
+    /// has returned [None] with [IncomparablePolicy::Poison](crate::IncomparablePolicy::Poison),
+    /// or because no element is comparable with
+    /// [IncomparablePolicy::Skip](crate::IncomparablePolicy::Skip).
+    Incomparable,
+    /// The index is not smaller than the length of the collection.
+    OutOfBounds { index: usize, len: usize },
+}
+
+impl fmt::Display for MinSmallVecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the collection is empty"),
+            Self::Incomparable => write!(f, "the collection contains incomparable elements"),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for length {len}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MinSmallVecError {}
@@ -0,0 +1,391 @@
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    tracker::{MinTracker, Order},
+    Compare, MinSmallVec, MinState,
+};
+
+/// A guard granting mutable access to the minimum value of a [MinSmallVec].
+///
+/// The min value is recalculated when the guard is dropped,
+/// but only if the value has been accessed mutably.
+/// Like [MinSmallVec::modify_single], this promotes the runner-up if it is tracked.
+///
+/// Created by [MinSmallVec::min_mut].
+pub struct MinMut<'a, T, const S: usize, C: Compare<T>> {
+    pub(crate) vec: &'a mut MinSmallVec<T, S, C>,
+    pub(crate) index: usize,
+    pub(crate) modified: bool,
+}
+
+impl<T, const S: usize, C: Compare<T>> MinMut<'_, T, S, C> {
+    /// Removes the minimum value from the collection and returns it.
+    pub fn pop(mut this: Self) -> T {
+        // `remove` already recalculates the min value
+        this.modified = false;
+        this.vec.remove(this.index)
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Deref for MinMut<'_, T, S, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.vec.inner[self.index]
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> DerefMut for MinMut<'_, T, S, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        &mut self.vec.inner[self.index]
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Drop for MinMut<'_, T, S, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            self.vec.update_modified(self.index, false);
+        }
+    }
+}
+
+/// An iterator over the elements removed by [MinSmallVec::drain].
+///
+/// The min value of the remaining elements is stored in the collection when the iterator is dropped.
+/// If the iterator is leaked, the collection is left without a min value, which is
+/// [MinState::Empty] until it is recalculated.
+pub struct Drain<'a, T, const S: usize> {
+    pub(crate) inner: smallvec::Drain<'a, [T; S]>,
+    pub(crate) min_slot: &'a mut MinTracker,
+    /// The min value of the remaining elements
+    pub(crate) min: MinTracker,
+}
+
+impl<T, const S: usize> Iterator for Drain<'_, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, const S: usize> DoubleEndedIterator for Drain<'_, T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, const S: usize> ExactSizeIterator for Drain<'_, T, S> {}
+
+impl<T, const S: usize> Drop for Drain<'_, T, S> {
+    fn drop(&mut self) {
+        *self.min_slot = self.min;
+    }
+}
+
+/// A guard granting mutable access to a single element of a [MinSmallVec].
+///
+/// The min value is updated when the guard is dropped,
+/// but only if the element has been accessed mutably.
+///
+/// Created by [MinSmallVec::get_mut].
+pub struct ElemMut<'a, T, const S: usize, C: Compare<T>> {
+    pub(crate) vec: &'a mut MinSmallVec<T, S, C>,
+    pub(crate) index: usize,
+    pub(crate) modified: bool,
+    /// Whether the element was equal to the min value before it was modified
+    pub(crate) was_equal: bool,
+}
+
+impl<T, const S: usize, C: Compare<T>> Deref for ElemMut<'_, T, S, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.vec.inner[self.index]
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> DerefMut for ElemMut<'_, T, S, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        if !self.modified {
+            self.was_equal = self.vec.is_equal_to_min(self.index);
+            self.modified = true;
+        }
+        &mut self.vec.inner[self.index]
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Drop for ElemMut<'_, T, S, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            self.vec.update_modified(self.index, self.was_equal);
+        }
+    }
+}
+
+/// State shared between an [IterMut] and the [ItemMut]s it yields.
+///
+/// The min value of the collection is updated when the state is dropped,
+/// which happens once the iterator and all of its items have been dropped.
+struct IterMutState<'a, T, const S: usize, C: Compare<T>> {
+    /// The min value of the collection, which is updated on drop
+    min_slot: &'a mut MinTracker,
+    order: &'a Order<C>,
+    /// Pointer to the first element, from which all element references are derived
+    base: *mut T,
+    len: usize,
+    /// The minimum before iterating
+    min: MinTracker,
+    /// Index of the smallest modified element whose guard has been dropped
+    best: Cell<Option<usize>>,
+    /// Number of modified elements equal to `best`
+    best_count: Cell<usize>,
+    /// Number of modified elements that were equal to the previous minimum before being modified
+    lost: Cell<usize>,
+    /// Whether the guard of the previous minimum is alive
+    min_alive: Cell<bool>,
+    /// Whether `lost` could not be determined because the guard of the previous minimum was alive
+    lost_unknown: Cell<bool>,
+    /// Whether any element has been modified
+    modified: Cell<bool>,
+    /// Whether the previous minimum has been modified
+    min_modified: Cell<bool>,
+    /// Whether [Compare::compare] has returned [None] comparing modified elements
+    incomparable: Cell<bool>,
+    _marker: PhantomData<&'a mut SmallVec<[T; S]>>,
+}
+
+impl<T, const S: usize, C: Compare<T>> IterMutState<'_, T, S, C> {
+    /// Records whether `val`, the element at `index`, is equal to the previous minimum
+    /// before it is modified for the first time.
+    ///
+    /// # Safety
+    /// No references to the element of the previous minimum may be alive unless `min_alive` is set.
+    unsafe fn prepare(&self, index: usize, val: &T) {
+        let MinState::Min(min) = self.min.state else {
+            return;
+        };
+
+        if min == index || self.min.count <= 1 {
+            return;
+        }
+
+        if self.min_alive.get() {
+            self.lost_unknown.set(true);
+            return;
+        }
+
+        // SAFETY: guaranteed by the caller
+        let min_val = unsafe { &*self.base.add(min) };
+
+        if self.order.compare(min_val, val) == Some(Ordering::Equal) {
+            self.lost.set(self.lost.get() + 1);
+        }
+    }
+
+    /// Records that `val`, the element at `index`, has been modified.
+    ///
+    /// # Safety
+    /// No references to the elements of previously recorded indices may be alive.
+    unsafe fn record(&self, index: usize, val: &T) {
+        self.modified.set(true);
+
+        if self.min.state == MinState::Min(index) {
+            self.min_modified.set(true);
+            return;
+        }
+
+        let Some(best) = self.best.get() else {
+            self.best.set(Some(index));
+            self.best_count.set(1);
+            return;
+        };
+
+        // SAFETY: guaranteed by the caller
+        let best_val = unsafe { &*self.base.add(best) };
+
+        match self.order.compare(val, best_val) {
+            Some(Ordering::Less) => {
+                self.best.set(Some(index));
+                self.best_count.set(1);
+            }
+            Some(Ordering::Equal) => {
+                self.best.set(Some(self.order.tie_break(index, best)));
+                self.best_count.set(self.best_count.get() + 1);
+            }
+            Some(Ordering::Greater) => {}
+            None => self.incomparable.set(true),
+        }
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Drop for IterMutState<'_, T, S, C> {
+    fn drop(&mut self) {
+        if !self.modified.get() {
+            return;
+        }
+
+        // SAFETY: the iterator and all items have been dropped,
+        // so there are no other references to the elements
+        let slice = unsafe { std::slice::from_raw_parts(self.base, self.len) };
+
+        *self.min_slot = match (self.min.state, self.best.get()) {
+            _ if self.min_modified.get() || self.lost_unknown.get() => self.order.slice_min(slice),
+            (MinState::Min(_), _) if self.incomparable.get() => MinTracker::INCOMPARABLE,
+            (MinState::Min(min), Some(best)) => {
+                let unmodified = MinTracker::min(min, self.min.count - self.lost.get());
+                let modified = match self.order.min_state(best, &slice[best]) {
+                    MinState::Min(best) => MinTracker::min(best, self.best_count.get()),
+                    _ => MinTracker::INCOMPARABLE,
+                };
+                self.order.combine(slice, unmodified, modified)
+            }
+            _ => self.order.slice_min(slice),
+        };
+    }
+}
+
+/// An iterator of guards granting mutable access to each element of a [MinSmallVec].
+///
+/// Created by [MinSmallVec::iter_mut].
+pub struct IterMut<'a, T, const S: usize, C: Compare<T>> {
+    state: Rc<IterMutState<'a, T, S, C>>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, const S: usize, C: Compare<T>> IterMut<'a, T, S, C> {
+    pub(crate) fn new(vec: &'a mut MinSmallVec<T, S, C>) -> Self {
+        let len = vec.inner.len();
+        let min = vec.min;
+
+        Self {
+            state: Rc::new(IterMutState {
+                min_slot: &mut vec.min,
+                order: &vec.order,
+                base: vec.inner.as_mut_ptr(),
+                len,
+                min,
+                best: Cell::new(None),
+                best_count: Cell::new(0),
+                lost: Cell::new(0),
+                min_alive: Cell::new(false),
+                lost_unknown: Cell::new(false),
+                modified: Cell::new(false),
+                min_modified: Cell::new(false),
+                incomparable: Cell::new(false),
+                _marker: PhantomData,
+            }),
+            front: 0,
+            back: len,
+        }
+    }
+
+    fn item(&self, index: usize) -> ItemMut<'a, T, S, C> {
+        if self.state.min.state == MinState::Min(index) {
+            self.state.min_alive.set(true);
+        }
+
+        ItemMut {
+            // SAFETY: `index` is in bounds
+            elem: unsafe { self.state.base.add(index) },
+            index,
+            modified: false,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<'a, T, const S: usize, C: Compare<T>> Iterator for IterMut<'a, T, S, C> {
+    type Item = ItemMut<'a, T, S, C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            self.front += 1;
+            self.item(self.front - 1)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> DoubleEndedIterator for IterMut<'_, T, S, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            self.back -= 1;
+            self.item(self.back)
+        })
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> ExactSizeIterator for IterMut<'_, T, S, C> {}
+
+/// A guard granting mutable access to an element yielded by [IterMut].
+///
+/// See [MinSmallVec::iter_mut] for how the min value is updated.
+pub struct ItemMut<'a, T, const S: usize, C: Compare<T>> {
+    /// Pointer to the element, which is only dereferenced while the guard is borrowed,
+    /// so that no reference to it outlives the guard when the state is dropped
+    elem: *mut T,
+    index: usize,
+    modified: bool,
+    state: Rc<IterMutState<'a, T, S, C>>,
+}
+
+impl<T, const S: usize, C: Compare<T>> ItemMut<'_, T, S, C> {
+    /// Returns the index of the element.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Deref for ItemMut<'_, T, S, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: each index is only yielded once, so this guard has exclusive access
+        unsafe { &*self.elem }
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> DerefMut for ItemMut<'_, T, S, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        if !self.modified {
+            // SAFETY: `min_alive` is set while the guard of the previous minimum is alive
+            unsafe { self.state.prepare(self.index, &*self.elem) };
+            self.modified = true;
+        }
+        // SAFETY: each index is only yielded once, so this guard has exclusive access
+        unsafe { &mut *self.elem }
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Drop for ItemMut<'_, T, S, C> {
+    fn drop(&mut self) {
+        if self.state.min.state == MinState::Min(self.index) {
+            self.state.min_alive.set(false);
+        }
+
+        if self.modified {
+            // SAFETY: the guards of previously recorded elements have been dropped,
+            // and this guard has exclusive access to its element
+            unsafe { self.state.record(self.index, &*self.elem) };
+        }
+    }
+}
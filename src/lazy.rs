@@ -0,0 +1,420 @@
+use std::{
+    ops::{Deref, Index, IndexMut, Range},
+    slice::SliceIndex,
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    tracker::{MinTracker, Order},
+    Compare, IncomparablePolicy, MinSmallVec, MinSmallVecError, MinState, Natural, TiePolicy,
+};
+
+/// A collection with a minimum value backed by a [SmallVec], which is only recalculated when requested.
+///
+/// Mutations mark the range of indices they touch as dirty instead of updating the min value.
+/// The next call to [LazyMinSmallVec::get_min] or any other method reading the min value
+/// compares the smallest dirty element with the minimum of the remaining elements, which is only
+/// recalculated using a linear scan if it has been marked dirty itself.
+/// This is cheaper than a [MinSmallVec] if many elements are modified between reads of the min value.
+///
+/// Since the min value does not have to be updated after each modification,
+/// elements can be modified through [IndexMut] and [LazyMinSmallVec::get_mut].
+///
+/// ```rust
+/// # use min_smallvec::LazyMinSmallVec;
+/// let mut vec = LazyMinSmallVec::<u32, 4>::new();
+/// vec.extend([5, 3, 8, 6]);
+/// assert_eq!(vec.get_min(), Some(&3));
+/// vec[2] = 1;
+/// vec[3] = 2;
+/// assert_eq!(vec.dirty_range(), Some(2..4));
+/// assert_eq!(vec.get_min(), Some(&1));
+/// assert!(!vec.is_dirty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyMinSmallVec<T, const S: usize, C = Natural> {
+    inner: SmallVec<[T; S]>,
+    /// State of the min value of the elements outside of `dirty`
+    min: MinTracker,
+    /// Range of the elements that may have been modified since the min value was last updated
+    dirty: Option<Range<usize>>,
+    order: Order<C>,
+}
+
+impl<T: PartialOrd, const S: usize> LazyMinSmallVec<T, S> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates an empty collection treating incomparable elements according to `policy`.
+    pub fn with_policy(policy: IncomparablePolicy) -> Self {
+        let mut vec = Self::new();
+        vec.order.policy = policy;
+        vec
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> LazyMinSmallVec<T, S, C> {
+    /// Creates an empty collection whose minimum is determined by `cmp`.
+    pub fn with_comparator(cmp: C) -> Self {
+        Self {
+            inner: SmallVec::new(),
+            min: MinTracker::EMPTY,
+            dirty: None,
+            order: Order::new(cmp),
+        }
+    }
+
+    /// Get a reference to the comparator.
+    pub fn comparator(&self) -> &C {
+        &self.order.cmp
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns a slice of all elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+
+    /// Returns an iterator over references to all elements.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    /// Returns `true` if the min value has to be updated before it can be read.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    /// Returns the range of elements that may have been modified since the min value was last updated.
+    pub fn dirty_range(&self) -> Option<Range<usize>> {
+        let range = self.dirty.clone()?;
+        Some(range.start..range.end.min(self.inner.len()))
+    }
+
+    /// Updates the min value if elements have been modified.
+    pub fn update_min(&mut self) {
+        self.order
+            .update_dirty(&mut self.min, &mut self.dirty, &self.inner);
+    }
+
+    /// Get a reference to the minimum value, updating it first if necessary.
+    ///
+    /// Returns [None] if the collection is empty or if the min value is [MinState::Incomparable].
+    pub fn get_min(&mut self) -> Option<&T> {
+        self.get_min_entry().map(|(_, min)| min)
+    }
+
+    /// Get the index of the minimum value, updating it first if necessary.
+    pub fn get_min_index(&mut self) -> Option<usize> {
+        self.min_state().index()
+    }
+
+    /// Get the index of the minimum value together with a reference to it,
+    /// updating it first if necessary.
+    pub fn get_min_entry(&mut self) -> Option<(usize, &T)> {
+        self.min_state()
+            .index()
+            .map(|index| (index, &self.inner[index]))
+    }
+
+    /// Get the state of the minimum value, updating it first if necessary.
+    pub fn min_state(&mut self) -> MinState {
+        self.update_min();
+        self.min.state
+    }
+
+    /// Returns the number of elements equal to the minimum value, updating it first if necessary.
+    pub fn min_count(&mut self) -> usize {
+        self.update_min();
+        self.min.count
+    }
+
+    /// Get the policy for incomparable elements.
+    pub fn policy(&self) -> IncomparablePolicy {
+        self.order.policy
+    }
+
+    /// Sets the policy for incomparable elements and marks all elements dirty.
+    pub fn set_policy(&mut self, policy: IncomparablePolicy) {
+        self.order.policy = policy;
+        self.mark_all_dirty();
+    }
+
+    /// Get the policy deciding which of several equal elements is the min value.
+    pub fn tie_policy(&self) -> TiePolicy {
+        self.order.tie
+    }
+
+    /// Sets the policy deciding which of several equal elements is the min value
+    /// and marks all elements dirty.
+    pub fn set_tie_policy(&mut self, tie: TiePolicy) {
+        self.order.tie = tie;
+        self.mark_all_dirty();
+    }
+
+    /// Extends the dirty range to cover `range` before the elements in it are modified.
+    fn mark_dirty(&mut self, range: Range<usize>) {
+        self.order
+            .mark_dirty(&mut self.min, &mut self.dirty, &self.inner, range);
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.mark_dirty(0..self.inner.len());
+    }
+
+    /// Applies a modification function to the elements and marks all of them dirty.
+    pub fn modify(&mut self, mut func: impl FnMut(&mut SmallVec<[T; S]>)) {
+        func(&mut self.inner);
+
+        // `func` may have added or removed elements, so the min value is recalculated from scratch
+        self.min = MinTracker::EMPTY;
+        self.dirty = Some(0..self.inner.len());
+    }
+
+    /// Modifies a single element and marks it dirty.
+    ///
+    /// Panics if the collection is empty or if `index` is out of bounds.
+    /// See [LazyMinSmallVec::try_modify_single] for a non-panicking version.
+    pub fn modify_single(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        if let Err(err) = self.try_modify_single(index, func) {
+            panic!("{err}");
+        }
+    }
+
+    /// Modifies a single element like [LazyMinSmallVec::modify_single],
+    /// but returns an error instead of panicking. `func` is not called if an error is returned.
+    pub fn try_modify_single(
+        &mut self,
+        index: usize,
+        mut func: impl FnMut(&mut T),
+    ) -> Result<(), MinSmallVecError> {
+        let len = self.inner.len();
+
+        if len == 0 {
+            return Err(MinSmallVecError::Empty);
+        }
+
+        if index >= len {
+            return Err(MinSmallVecError::OutOfBounds { index, len });
+        }
+
+        self.mark_dirty(index..index + 1);
+        func(&mut self.inner[index]);
+
+        Ok(())
+    }
+
+    /// Modifies the elements at `indices` and marks them dirty.
+    ///
+    /// Like [MinSmallVec::modify_many], the dirty range covers all elements between the smallest
+    /// and the largest index.
+    ///
+    /// Panics if any index is out of bounds, after the elements at the previous indices have been modified.
+    pub fn modify_many(
+        &mut self,
+        indices: impl IntoIterator<Item = usize>,
+        mut func: impl FnMut(&mut T),
+    ) {
+        for index in indices {
+            self.modify_single(index, &mut func);
+        }
+    }
+
+    /// Get a mutable reference to the element at `index` after marking it dirty,
+    /// or [None] if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.inner.len() {
+            return None;
+        }
+
+        self.mark_dirty(index..index + 1);
+        Some(&mut self.inner[index])
+    }
+
+    /// Returns a mutable slice of all elements after marking all of them dirty.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.mark_all_dirty();
+        &mut self.inner
+    }
+
+    /// Pushes a value and marks it dirty.
+    pub fn push(&mut self, value: T) {
+        let len = self.inner.len();
+        self.mark_dirty(len..len + 1);
+        self.inner.push(value);
+    }
+
+    /// Inserts a value at `index`, shifting all elements after it to the right,
+    /// and marks all of them dirty.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.inner.len();
+        assert!(index <= len, "insertion index out of bounds");
+
+        self.mark_dirty(index..len + 1);
+        self.inner.insert(index, value);
+    }
+
+    /// Removes the last element and returns it, or [None] if the collection is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.inner.len().checked_sub(1)?;
+        Some(self.remove(index))
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left,
+    /// and marks all of them dirty.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.inner.len();
+        assert!(index < len, "index out of bounds");
+
+        self.mark_dirty(index..len);
+        self.inner.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element,
+    /// and marks all elements from `index` on dirty.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.inner.len();
+        assert!(index < len, "index out of bounds");
+
+        self.mark_dirty(index..len);
+        self.inner.swap_remove(index)
+    }
+
+    /// Removes and returns the minimum value, preserving the order of the remaining elements.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.get_min_index().map(|index| self.remove(index))
+    }
+
+    /// Shortens the collection to `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.inner.len() {
+            self.mark_dirty(len..self.inner.len());
+            self.inner.truncate(len);
+        }
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.min = MinTracker::EMPTY;
+        self.dirty = None;
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> Default for LazyMinSmallVec<T, S, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+/// Keeps the min value of `vec`, so no elements are dirty.
+impl<T, const S: usize, C> From<MinSmallVec<T, S, C>> for LazyMinSmallVec<T, S, C> {
+    fn from(vec: MinSmallVec<T, S, C>) -> Self {
+        Self {
+            inner: vec.inner,
+            min: vec.min,
+            dirty: None,
+            order: vec.order,
+        }
+    }
+}
+
+/// Updates the min value of `vec` if elements are dirty.
+impl<T, const S: usize, C: Compare<T>> From<LazyMinSmallVec<T, S, C>> for MinSmallVec<T, S, C> {
+    fn from(mut vec: LazyMinSmallVec<T, S, C>) -> Self {
+        vec.update_min();
+
+        Self {
+            inner: vec.inner,
+            min: vec.min,
+            order: vec.order,
+        }
+    }
+}
+
+impl<T, const S: usize, C> Deref for LazyMinSmallVec<T, S, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const S: usize, C> Index<I> for LazyMinSmallVec<T, S, C> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.inner[index]
+    }
+}
+
+/// Marks the element at `index` dirty.
+impl<T, const S: usize, C: Compare<T>> IndexMut<usize> for LazyMinSmallVec<T, S, C> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.inner.len();
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("{}", MinSmallVecError::OutOfBounds { index, len }))
+    }
+}
+
+impl<T, const S: usize, C> IntoIterator for LazyMinSmallVec<T, S, C> {
+    type Item = T;
+    type IntoIter = smallvec::IntoIter<[T; S]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T, const S: usize, C> IntoIterator for &'a LazyMinSmallVec<T, S, C> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Extend<T> for LazyMinSmallVec<T, S, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let len = self.inner.len();
+        self.inner.extend(iter);
+
+        if self.inner.len() > len {
+            // only the previous elements may have to be compared with the min value
+            self.order.mark_dirty(
+                &mut self.min,
+                &mut self.dirty,
+                &self.inner[..len],
+                len..self.inner.len(),
+            );
+        }
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> FromIterator<T> for LazyMinSmallVec<T, S, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::default();
+        vec.extend(iter);
+        vec
+    }
+}
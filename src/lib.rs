@@ -1,65 +1,123 @@
 //! # Min SmallVec
 //! A collection that knows its own minimum value.
 
-use std::ptr::NonNull;
+use std::{
+    cmp::Ordering,
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    slice::SliceIndex,
+};
 
 use smallvec::SmallVec;
+use tracker::{MinTracker, Order, RunnerUp};
+
+mod btree;
+mod by_key;
+mod compare;
+mod error;
+mod guard;
+mod lazy;
+mod min_max;
+#[cfg(test)]
+mod tests;
+mod tracker;
+mod tree;
+
+pub use btree::MinBTree;
+pub use by_key::MinByKeySmallVec;
+pub use compare::{
+    Compare, IncomparablePolicy, Natural, Reversed, TiePolicy, TotalCompare, TotalF32, TotalF64,
+};
+pub use error::MinSmallVecError;
+pub use guard::{Drain, ElemMut, ItemMut, IterMut, MinMut};
+pub use lazy::LazyMinSmallVec;
+pub use min_max::MinMaxSmallVec;
+pub use tree::MinTree;
 
 /// A collection with a known minimum value backed by a [SmallVec].
 ///
-/// Comparisons and equality on the type are delegated to comparisons and equality
-/// on the minimum value.
+/// The minimum is determined by the comparator `C`, which defaults to the [PartialOrd]
+/// implementation of `T`. Comparisons and equality on the type are delegated to
+/// comparisons of the minimum values using the comparator.
+/// Which of several equal elements is the minimum is decided by the [TiePolicy].
 ///
-/// This allows one to create a tree of [MinSmallVec]s like so:
-/// ```rust
-/// struct MinTree<
-///     T: PartialOrd,
-///     const OS: usize, // outer size
-///     const IS: usize, // inner size
-/// >(MinSmallVec<MinSmallVec<T, IS>, OS>);
-/// ```
+/// For floats, [TotalF64] and [TotalF32] give every value, including NaN, a defined order.
+/// With [PartialOrd], a NaN is handled according to the [IncomparablePolicy].
 ///
-/// which reduces the cost of computing the minimum value logarithmically.
+/// This allows one to create a tree of [MinSmallVec]s, which reduces the cost of computing
+/// the minimum value logarithmically. [MinTree] implements such a tree with two levels,
+/// and [MinBTree] one whose depth grows with the number of elements.
+///
+/// [MinSmallVec] is [Send] and [Sync] whenever the backing [SmallVec] is.
 #[derive(Debug)]
-pub struct MinSmallVec<T: PartialOrd, const S: usize> {
+pub struct MinSmallVec<T, const S: usize, C = Natural> {
     inner: SmallVec<[T; S]>,
-    /// Min value of the contained array is [None] if the array is empty
-    /// or [PartialOrd::partial_cmp] has returned [None]
-    min: Option<NonNull<T>>,
+    /// State of the min value of the contained array.
+    ///
+    /// An index is used instead of a pointer so that it stays valid when the
+    /// collection is moved or when the backing storage is reallocated.
+    min: MinTracker,
+    order: Order<C>,
 }
 
-fn slice_min<T: PartialOrd>(slice: &[T]) -> Option<NonNull<T>> {
-    let first = slice.first()?;
+/// A collection with a known maximum value backed by a [SmallVec].
+///
+/// This is a [MinSmallVec] with a [Reversed] comparator, so the methods referring to the
+/// minimum refer to the maximum, which can also be accessed using [MinSmallVec::get_max].
+///
+/// ```rust
+/// # use min_smallvec::MaxSmallVec;
+/// let mut vec = MaxSmallVec::<u32, 4>::default();
+/// vec.extend([3, 7, 5]);
+/// assert_eq!(vec.get_max(), Some(&7));
+/// ```
+pub type MaxSmallVec<T, const S: usize, C = Natural> = MinSmallVec<T, S, Reversed<C>>;
 
-    slice[1..]
-        .iter()
-        .try_fold(first, |min, val| {
-            min.partial_cmp(val).map(|ord| match ord {
-                std::cmp::Ordering::Greater => val,
-                _ => min,
-            })
-        })
-        .map(|refer: &T| refer.into())
+/// The state of the min value of a [MinSmallVec].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MinState {
+    /// The collection is empty.
+    Empty,
+    /// [Compare::compare] has returned [None] while calculating the min value
+    /// with [IncomparablePolicy::Poison], or no element is comparable with
+    /// [IncomparablePolicy::Skip].
+    ///
+    /// The min value is recalculated when an element is removed or modified,
+    /// as that element may have been the incomparable one.
+    Incomparable,
+    /// The min value is at the contained index.
+    Min(usize),
 }
 
-fn partial_min<T: PartialOrd>(lhs: &T, rhs: &T) -> Option<NonNull<T>> {
-    let ord = lhs.partial_cmp(rhs)?;
-
-    Some(
-        match ord {
-            std::cmp::Ordering::Greater => rhs,
-            _ => lhs,
+impl MinState {
+    /// Returns the index of the min value, if there is one.
+    pub fn index(self) -> Option<usize> {
+        match self {
+            Self::Min(index) => Some(index),
+            _ => None,
         }
-        .into(),
-    )
+    }
 }
 
+// Guards the documented auto trait implementations against regressions.
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    fn assert_min_smallvec() {
+        assert_send_sync::<MinSmallVec<u32, 4>>();
+    }
+};
+
 impl<T: PartialOrd, const S: usize> MinSmallVec<T, S> {
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            inner: SmallVec::with_capacity(capacity),
-            min: None,
-        }
+        Self::with_capacity_and_comparator(capacity, Natural)
+    }
+
+    /// Creates an empty collection treating incomparable elements according to `policy`.
+    pub fn with_policy(policy: IncomparablePolicy) -> Self {
+        let mut vec = Self::new();
+        vec.order.policy = policy;
+        vec
     }
 
     pub fn new() -> Self {
@@ -70,85 +128,912 @@ impl<T: PartialOrd, const S: usize> MinSmallVec<T, S> {
     where
         T: Copy,
     {
-        Self {
-            min: slice_min(slice),
+        let mut vec = Self {
             inner: SmallVec::from_slice(slice),
+            min: MinTracker::EMPTY,
+            order: Order::new(Natural),
+        };
+        vec.rescan();
+        vec
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> MinSmallVec<T, S, C> {
+    /// Creates an empty collection whose minimum is determined by `cmp`.
+    ///
+    /// ```rust
+    /// # use min_smallvec::MinSmallVec;
+    /// let mut vec = MinSmallVec::<(u32, &str), 4, _>::with_comparator(
+    ///     |a: &(u32, &str), b: &(u32, &str)| b.0.cmp(&a.0),
+    /// );
+    /// vec.extend([(1, "low"), (3, "high"), (2, "mid")]);
+    /// assert_eq!(vec.get_min(), Some(&(3, "high")));
+    /// ```
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_capacity_and_comparator(0, cmp)
+    }
+
+    /// Creates an empty collection with space for at least `capacity` elements
+    /// whose minimum is determined by `cmp`.
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        Self {
+            inner: SmallVec::with_capacity(capacity),
+            min: MinTracker::EMPTY,
+            order: Order::new(cmp),
         }
     }
 
+    /// Get a reference to the comparator.
+    pub fn comparator(&self) -> &C {
+        &self.order.cmp
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns `true` if the elements have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        self.inner.spilled()
+    }
+
+    /// Shrinks the capacity as much as possible,
+    /// moving the elements back inline if they fit.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Returns a slice of all elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+
+    /// Returns an iterator over references to all elements.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+
     /// Get a reference to the minimum value.
+    ///
+    /// Returns [None] if the collection is empty or if the min value is [MinState::Incomparable].
+    /// Use [MinSmallVec::try_get_min] to distinguish between the two.
+    /// With a [TotalCompare] comparator, like [Natural] for types implementing [Ord],
+    /// the min value is never incomparable, so this only returns [None] if the collection is empty.
     pub fn get_min(&self) -> Option<&T> {
-        // SAFETY: min always points to a value in self
-        self.min.map(|ptr| unsafe { ptr.as_ref() })
+        self.min.state.index().map(|index| &self.inner[index])
+    }
+
+    /// Get the index of the minimum value.
+    pub fn get_min_index(&self) -> Option<usize> {
+        self.min.state.index()
+    }
+
+    /// Get the index of the minimum value together with a reference to it.
+    pub fn get_min_entry(&self) -> Option<(usize, &T)> {
+        self.min
+            .state
+            .index()
+            .map(|index| (index, &self.inner[index]))
+    }
+
+    /// Get the state of the minimum value.
+    pub fn min_state(&self) -> MinState {
+        self.min.state
+    }
+
+    /// Returns the number of elements equal to the minimum value, including itself,
+    /// or `0` if there is no minimum value.
+    ///
+    /// ```rust
+    /// # use min_smallvec::MinSmallVec;
+    /// let mut vec = MinSmallVec::<u32, 4>::from_slice(&[2, 1, 3, 1]);
+    /// assert_eq!(vec.min_count(), 2);
+    /// assert_eq!(vec.pop_min(), Some(1));
+    /// // the other 1 is found without comparing it with every element
+    /// assert_eq!(vec.get_min_entry(), Some((2, &1)));
+    /// ```
+    pub fn min_count(&self) -> usize {
+        self.min.count
+    }
+
+    /// Returns an iterator over the indices of and references to all elements equal to the minimum value,
+    /// in ascending index order.
+    pub fn iter_minima(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        let min = self.get_min_entry();
+        let skip = match (min, self.order.tie) {
+            // the minimum is the first of the equal elements
+            (Some((index, _)), TiePolicy::First) => index,
+            _ => 0,
+        };
+
+        self.inner
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .filter(move |&(index, val)| {
+                min.is_some_and(|(min_index, min)| {
+                    index == min_index || self.order.is_equal(min, val)
+                })
+            })
+            .take(self.min.count)
+    }
+
+    /// Get the policy for incomparable elements.
+    pub fn policy(&self) -> IncomparablePolicy {
+        self.order.policy
+    }
+
+    /// Sets the policy for incomparable elements and recalculates the min value using a linear scan.
+    pub fn set_policy(&mut self, policy: IncomparablePolicy) {
+        self.order.policy = policy;
+        self.rescan();
+    }
+
+    /// Get the policy deciding which of several equal elements is the min value.
+    pub fn tie_policy(&self) -> TiePolicy {
+        self.order.tie
+    }
+
+    /// Sets the policy deciding which of several equal elements is the min value
+    /// and recalculates the min value using a linear scan.
+    pub fn set_tie_policy(&mut self, tie: TiePolicy) {
+        self.order.tie = tie;
+        self.rescan();
+    }
+
+    /// Returns `true` if the runner-up, the smallest element other than the min value, is tracked.
+    pub fn runner_up_tracking(&self) -> bool {
+        self.order.track_runner_up
+    }
+
+    /// Enables or disables tracking the runner-up and recalculates the min value using a linear scan.
+    ///
+    /// With tracking enabled, every scan also determines the runner-up and updates keep it when possible.
+    /// Removing the min value with [MinSmallVec::pop_min] or increasing it with [MinSmallVec::modify_single]
+    /// then promotes the runner-up instead of scanning. As the new runner-up is not known afterwards,
+    /// every other such operation in a row still scans, which also happens when the runner-up is modified or removed.
+    ///
+    /// ```rust
+    /// # use min_smallvec::MinSmallVec;
+    /// let mut vec = MinSmallVec::<u32, 4>::new();
+    /// vec.set_runner_up_tracking(true);
+    /// vec.extend([5, 2, 8, 3]);
+    /// assert_eq!(vec.pop_min(), Some(2));
+    /// assert_eq!(vec.get_min(), Some(&3));
+    /// ```
+    pub fn set_runner_up_tracking(&mut self, enabled: bool) {
+        self.order.track_runner_up = enabled;
+        self.rescan();
+    }
+
+    /// Recalculates the min value using a linear scan.
+    fn rescan(&mut self) {
+        self.min = self.order.slice_min(&self.inner);
+    }
+
+    /// Moves the min value to its new index given by `map` after the elements have been permuted.
+    ///
+    /// Returns `false` without updating the min value if there are elements equal to it,
+    /// whose order may have changed.
+    fn relocate_min(&mut self, map: impl Fn(usize) -> usize) -> bool {
+        let (MinState::Min(min), 1) = (self.min.state, self.min.count) else {
+            return false;
+        };
+
+        self.min.state = MinState::Min(map(min));
+        self.min.runner_up = match self.min.runner_up {
+            RunnerUp::Known(r, 1) => RunnerUp::Known(map(r), 1),
+            RunnerUp::Absent => RunnerUp::Absent,
+            _ => RunnerUp::Unknown,
+        };
+        true
+    }
+
+    /// Get a reference to the minimum value, or the reason there is none.
+    pub fn try_get_min(&self) -> Result<&T, MinSmallVecError> {
+        self.try_get_min_entry().map(|(_, min)| min)
+    }
+
+    /// Get the index of the minimum value together with a reference to it, or the reason there is none.
+    pub fn try_get_min_entry(&self) -> Result<(usize, &T), MinSmallVecError> {
+        self.try_min_index()
+            .map(|index| (index, &self.inner[index]))
+    }
+
+    /// Get the index of the minimum value, or the reason there is none.
+    fn try_min_index(&self) -> Result<usize, MinSmallVecError> {
+        match self.min.state {
+            MinState::Empty => Err(MinSmallVecError::Empty),
+            MinState::Incomparable => Err(MinSmallVecError::Incomparable),
+            MinState::Min(index) => Ok(index),
+        }
+    }
+
+    /// Get a guard granting mutable access to the minimum value.
+    /// The min value is recalculated when the guard is dropped if the value was modified.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn min_mut(&mut self) -> Option<MinMut<'_, T, S, C>> {
+        self.min.state.index().map(|index| MinMut {
+            vec: self,
+            index,
+            modified: false,
+        })
     }
 
     /// Applies a modification function to `self` and recalculates the min value after
     /// using a linear scan
     pub fn modify(&mut self, mut func: impl FnMut(&mut SmallVec<[T; S]>)) {
         func(&mut self.inner);
-        self.min = slice_min(&self.inner);
+        self.rescan();
     }
 
     /// Modifies a single element. This is cheaper than using [MinSmallVec::modify]
-    /// if the modified element is not equal to the minimum value.
-    pub fn modify_single(&mut self, index: usize, mut func: impl FnMut(&mut T)) {
-        let min = self.get_min().unwrap();
-        let was_min = min == &self.inner[index];
+    /// if the modified element is not the minimum value.
+    ///
+    /// If the min value is [MinState::Incomparable], it is recalculated using a linear scan,
+    /// as the modified element may have been the incomparable one.
+    ///
+    /// Panics if the collection is empty or if `index` is out of bounds.
+    /// See [MinSmallVec::try_modify_single] for a non-panicking version.
+    pub fn modify_single(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        if let Err(err) = self.try_modify_single(index, func) {
+            panic!("{err}");
+        }
+    }
+
+    /// Modifies a single element like [MinSmallVec::modify_single],
+    /// but returns an error instead of panicking. `func` is not called if an error is returned.
+    pub fn try_modify_single(
+        &mut self,
+        index: usize,
+        mut func: impl FnMut(&mut T),
+    ) -> Result<(), MinSmallVecError> {
+        let len = self.inner.len();
+
+        if len == 0 {
+            return Err(MinSmallVecError::Empty);
+        }
+
+        if index >= len {
+            return Err(MinSmallVecError::OutOfBounds { index, len });
+        }
+
+        let was_equal = self.is_equal_to_min(index);
         func(&mut self.inner[index]);
+        self.update_modified(index, was_equal);
 
-        if was_min {
-            self.min = slice_min(&self.inner);
-        } else {
-            self.min = partial_min(self.get_min().unwrap(), &self.inner[index]);
+        Ok(())
+    }
+
+    /// Modifies the elements at `indices` and updates the min value once afterwards.
+    ///
+    /// The min value is only recalculated using a linear scan if it was modified,
+    /// otherwise it is compared with the smallest element between the smallest and the largest index,
+    /// so this is cheapest for indices close to each other.
+    /// An index may be given multiple times, in which case the element is modified multiple times.
+    ///
+    /// Panics if any index is out of bounds, after the elements at the previous indices have been modified.
+    ///
+    /// ```rust
+    /// # use min_smallvec::MinSmallVec;
+    /// let mut vec = MinSmallVec::<u32, 4>::new();
+    /// vec.extend([4, 6, 8, 2]);
+    /// vec.modify_many([0, 1, 2], |val| *val /= 2);
+    /// assert_eq!(vec.as_slice(), &[2, 3, 4, 2]);
+    /// assert_eq!(vec.get_min_index(), Some(0));
+    /// ```
+    pub fn modify_many(
+        &mut self,
+        indices: impl IntoIterator<Item = usize>,
+        mut func: impl FnMut(&mut T),
+    ) {
+        let mut dirty = None;
+
+        for index in indices {
+            let len = self.inner.len();
+
+            if index >= len {
+                self.order
+                    .update_dirty(&mut self.min, &mut dirty, &self.inner);
+                panic!("{}", MinSmallVecError::OutOfBounds { index, len });
+            }
+
+            self.order
+                .mark_dirty(&mut self.min, &mut dirty, &self.inner, index..index + 1);
+            func(&mut self.inner[index]);
         }
+
+        self.order
+            .update_dirty(&mut self.min, &mut dirty, &self.inner);
+    }
+
+    /// Get a guard granting mutable access to the element at `index`,
+    /// or [None] if `index` is out of bounds.
+    ///
+    /// When dropped, the guard updates the min value the same way as [MinSmallVec::modify_single]
+    /// if the element was modified.
+    ///
+    /// There is no [IndexMut](std::ops::IndexMut) implementation, because it would have to hand out
+    /// a plain `&mut T` after which the min value can not be updated.
+    pub fn get_mut(&mut self, index: usize) -> Option<ElemMut<'_, T, S, C>> {
+        (index < self.inner.len()).then(|| ElemMut {
+            vec: self,
+            index,
+            modified: false,
+            was_equal: false,
+        })
+    }
+
+    /// Returns an iterator of guards granting mutable access to each element.
+    ///
+    /// When a guard is dropped after its element was modified, the element is compared with the
+    /// other modified elements. Once the iterator and all guards have been dropped, the min value is
+    /// updated by comparing the smallest modified element with the previous minimum,
+    /// or using a linear scan if the previous minimum was modified.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, S, C> {
+        IterMut::new(self)
+    }
+
+    /// Updates the min value after the element at `index` has been modified.
+    ///
+    /// `was_equal` is the result of [MinSmallVec::is_equal_to_min] before the modification.
+    fn update_modified(&mut self, index: usize, was_equal: bool) {
+        self.order
+            .update_modified(&mut self.min, &self.inner, index, was_equal);
+    }
+
+    /// Returns `true` if the element at `index` is not the min value but equal to it.
+    fn is_equal_to_min(&self, index: usize) -> bool {
+        self.order.is_equal_to_min(&self.min, &self.inner, index)
     }
 
-    /// Pushes a value. This is faster than using [MinBucket::modify]
+    /// Pushes a value. This is faster than using [MinSmallVec::modify]
     pub fn push(&mut self, value: T) {
         self.inner.push(value);
-        let pushed = unsafe { self.inner.last().unwrap_unchecked() };
+        self.update_inserted(self.inner.len() - 1, 1);
+    }
+
+    /// Inserts a value at `index`, shifting all elements after it to the right.
+    /// Like [MinSmallVec::push], only the inserted value is compared with the minimum.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.inner.insert(index, value);
+        self.update_inserted(index, 1);
+    }
+
+    /// Inserts all values yielded by `iterable` at `index`, shifting all elements after them to the right.
+    /// Only the inserted values are compared with the minimum.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert_many<I: IntoIterator<Item = T>>(&mut self, index: usize, iterable: I) {
+        let len = self.inner.len();
+        self.inner.insert_many(index, iterable);
+        self.update_inserted(index, self.inner.len() - len);
+    }
+
+    /// Appends all values of `slice`. Only the appended values are compared with the minimum.
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where
+        T: Copy,
+    {
+        let len = self.inner.len();
+        self.inner.extend_from_slice(slice);
+        self.update_inserted(len, slice.len());
+    }
+
+    /// Updates the min value after `count` values have been inserted at `index`
+    /// by comparing only the inserted values with the current minimum.
+    fn update_inserted(&mut self, index: usize, count: usize) {
+        self.order
+            .update_inserted(&mut self.min, &self.inner, index, count);
+    }
+
+    /// Removes the last element and returns it, or [None] if the collection is empty.
+    ///
+    /// The min value is only recalculated if the removed element was the minimum
+    /// and there is no other element equal to it.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.inner.len().checked_sub(1)?;
+        Some(self.remove(index))
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// The min value is only recalculated if the removed element was the minimum
+    /// and there is no other element equal to it.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.inner.len(), "index out of bounds");
 
-        // if len was 0 (now 1 due to pushing),
-        // then self.min was `None` due to there being no elements
-        if (self.inner.len() == 1) || self.get_min().is_some_and(|min| pushed < min) {
-            self.min = Some(pushed.into());
+        self.update_removed(index..index + 1);
+        self.inner.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    /// This does not preserve ordering, but is O(1) if the removed element is not the minimum.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let value = self.inner.swap_remove(index);
+        self.order
+            .update_swap_removed(&mut self.min, &self.inner, index, &value);
+        value
+    }
+
+    /// Removes and returns the minimum value, preserving the order of the remaining elements.
+    /// The new min value is the next element equal to it if there is one,
+    /// and is otherwise calculated using a single linear scan.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.try_pop_min().ok()
+    }
+
+    /// Removes and returns the minimum value like [MinSmallVec::pop_min], or the reason there is none.
+    pub fn try_pop_min(&mut self) -> Result<T, MinSmallVecError> {
+        self.try_min_index().map(|min| self.remove(min))
+    }
+
+    /// Removes and returns the minimum value, replacing it with the last element.
+    /// The new min value is the next element equal to it if there is one,
+    /// and is otherwise calculated using a single linear scan.
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn swap_remove_min(&mut self) -> Option<T> {
+        self.min.state.index().map(|min| self.swap_remove(min))
+    }
+
+    /// Removes the elements in `range` and returns them as an iterator.
+    ///
+    /// The min value is only recalculated if it is inside `range`,
+    /// in which case only the remaining elements are scanned.
+    /// See [Drain] for what happens if the returned iterator is leaked.
+    ///
+    /// Panics if the start of `range` is greater than its end or if its end is greater than `len`.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, S> {
+        let range = self.resolve_range(range);
+        self.update_removed(range.clone());
+
+        // the new min value is only stored once the removed elements are gone
+        let min = std::mem::replace(&mut self.min, MinTracker::EMPTY);
+        Drain {
+            inner: self.inner.drain(range),
+            min_slot: &mut self.min,
+            min,
+        }
+    }
+
+    /// Removes the elements in `range` and inserts the values yielded by `replace_with` in their place.
+    /// Returns the removed elements.
+    ///
+    /// This is equivalent to [MinSmallVec::drain] followed by [MinSmallVec::insert_many].
+    ///
+    /// Panics if the start of `range` is greater than its end or if its end is greater than `len`.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> smallvec::IntoIter<[T; S]>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let range = self.resolve_range(range);
+        let start = range.start;
+        let removed: SmallVec<[T; S]> = self.drain(range).collect();
+        self.insert_many(start, replace_with);
+        removed.into_iter()
+    }
+
+    /// Retains only the elements for which `pred` returns `true`, preserving their order.
+    ///
+    /// The min value is only recalculated if it was removed or if there are elements equal to it,
+    /// whose number may have changed.
+    pub fn retain(&mut self, mut pred: impl FnMut(&T) -> bool) {
+        let (MinState::Min(min), 1) = (self.min.state, self.min.count) else {
+            self.inner.retain(|val| pred(val));
+            self.rescan();
+            return;
+        };
+
+        let mut index = 0;
+        let mut kept = 0;
+        let mut new_min = None;
+
+        self.inner.retain(|val| {
+            let keep = pred(val);
+
+            if keep {
+                if index == min {
+                    new_min = Some(kept);
+                }
+                kept += 1;
+            }
+            index += 1;
+            keep
+        });
+
+        self.min = match new_min {
+            Some(min) => MinTracker::min(min, 1),
+            None => self.order.slice_min(&self.inner),
+        };
+    }
+
+    /// Retains only the elements for which `pred` returns `true`, preserving their order.
+    ///
+    /// Since `pred` may modify any element, the min value is always recalculated
+    /// using a linear scan.
+    pub fn retain_mut(&mut self, pred: impl FnMut(&mut T) -> bool) {
+        self.inner.retain_mut(pred);
+        self.rescan();
+    }
+
+    /// Shortens the collection to `len` elements. Does nothing if `len` is greater than the current length.
+    ///
+    /// The min value is only recalculated if it was removed and there is no other element equal to it.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.inner.len() {
+            return;
+        }
+
+        self.update_removed(len..self.inner.len());
+        self.inner.truncate(len);
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.min = MinTracker::EMPTY;
+    }
+
+    /// Swaps the elements at indices `a` and `b`.
+    ///
+    /// If the minimum is one of them and there are elements equal to it, the elements between
+    /// `a` and `b` are scanned, as an equal element may take precedence according to the [TiePolicy].
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.inner.swap(a, b);
+
+        match self.min.state {
+            MinState::Min(min) if min == a || min == b => {
+                // without equal elements, the minimum simply moves to the other index
+                if self.relocate_min(|i| {
+                    if i == a {
+                        b
+                    } else if i == b {
+                        a
+                    } else {
+                        i
+                    }
+                }) {
+                    return;
+                }
+
+                // equal elements between `a` and `b` may take precedence over the moved minimum
+                let (start, end) = (a.min(b), a.max(b));
+                let range = self.inner[start..=end].iter().enumerate();
+                self.min.state = self
+                    .order
+                    .slice_min_in(range.map(|(i, val)| (start + i, val)))
+                    .state;
+                self.min.runner_up = RunnerUp::Unknown;
+            }
+            // the runner-up has been moved, which may reorder the elements equal to it
+            MinState::Min(_) if matches!(self.min.runner_up, RunnerUp::Known(r, _) if r == a || r == b) =>
+            {
+                self.min.runner_up = RunnerUp::Unknown;
+                self.order.update_moved(&mut self.min, &self.inner, a);
+                self.order.update_moved(&mut self.min, &self.inner, b);
+            }
+            // the swapped elements may be equal to the minimum
+            MinState::Min(_) => {
+                self.order.update_moved(&mut self.min, &self.inner, a);
+                self.order.update_moved(&mut self.min, &self.inner, b);
+            }
+            _ => {}
+        }
+    }
+
+    /// Reverses the order of the elements.
+    ///
+    /// The min value is recalculated using a linear scan if there are elements equal to it,
+    /// as the order of equal elements is reversed.
+    pub fn reverse(&mut self) {
+        self.inner.reverse();
+
+        let len = self.inner.len();
+        if !self.relocate_min(|i| len - 1 - i) {
+            self.rescan();
+        }
+    }
+
+    /// Rotates the elements `mid` places to the left.
+    ///
+    /// The min value is recalculated using a linear scan if there are elements equal to it,
+    /// as elements may move in front of equal ones.
+    ///
+    /// Panics if `mid > len`.
+    pub fn rotate_left(&mut self, mid: usize) {
+        self.inner.rotate_left(mid);
+
+        let len = self.inner.len();
+        if !self.relocate_min(|i| (i + len - mid) % len) {
+            self.rescan();
+        }
+    }
+
+    /// Rotates the elements `k` places to the right.
+    ///
+    /// The min value is recalculated using a linear scan if there are elements equal to it,
+    /// as elements may move in front of equal ones.
+    ///
+    /// Panics if `k > len`.
+    pub fn rotate_right(&mut self, k: usize) {
+        self.inner.rotate_right(k);
+
+        let len = self.inner.len();
+        if !self.relocate_min(|i| (i + k) % len) {
+            self.rescan();
         }
-        // else the min value is `None` due to a partial_cmp call returning `None`
+    }
+
+    /// Sorts the elements with a comparison function, preserving the order of equal elements.
+    ///
+    /// Since `compare` may order the elements differently from the comparator,
+    /// the min value is recalculated using a linear scan.
+    pub fn sort_by(&mut self, compare: impl FnMut(&T, &T) -> Ordering) {
+        self.inner.sort_by(compare);
+        self.rescan();
+    }
+
+    /// Removes consecutive elements which compare equal according to the comparator.
+    ///
+    /// If the minimum is removed, the equal element it was a duplicate of becomes the minimum.
+    pub fn dedup(&mut self) {
+        let order = &self.order;
+        let MinState::Min(min) = self.min.state else {
+            self.inner.dedup_by(|a, b| order.is_equal(b, a));
+            self.rescan();
+            return;
+        };
+
+        // like `dedup_by`, every element is compared with the last one that is kept
+        let (mut last, mut removed, mut removed_equal) = (0, 0, 0);
+
+        for i in 1..self.inner.len() {
+            if !order.is_equal(&self.inner[last], &self.inner[i]) {
+                last = i;
+                continue;
+            }
+
+            // every removed element before or at `min` shifts it one place to the left
+            if i <= min {
+                removed += 1;
+            }
+            if i == min || self.is_equal_to_min(i) {
+                removed_equal += 1;
+            }
+        }
+
+        self.min = MinTracker::min(min - removed, self.min.count - removed_equal);
+        self.inner.dedup_by(|a, b| order.is_equal(b, a));
+    }
+
+    /// Converts any [RangeBounds] into a [Range], panicking if it is invalid for `self`.
+    fn resolve_range(&self, range: impl RangeBounds<usize>) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("Range start out of bounds"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("Range end out of bounds"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.inner.len(),
+        };
+
+        assert!(start <= end);
+        assert!(end <= self.inner.len());
+        start..end
+    }
+
+    /// Updates the min value before the elements in `range` are removed
+    /// by scanning the remaining elements only if the minimum may be one of the removed ones.
+    fn update_removed(&mut self, range: Range<usize>) {
+        self.order.update_removed(&mut self.min, &self.inner, range);
     }
 }
 
-impl<T: PartialOrd, const S: usize> Default for MinSmallVec<T, S> {
+impl<T, const S: usize, C: Compare<T>> MaxSmallVec<T, S, C> {
+    /// Get a reference to the maximum value.
+    pub fn get_max(&self) -> Option<&T> {
+        self.get_min()
+    }
+
+    /// Get the index of the maximum value.
+    pub fn get_max_index(&self) -> Option<usize> {
+        self.get_min_index()
+    }
+
+    /// Get the index of the maximum value together with a reference to it.
+    pub fn get_max_entry(&self) -> Option<(usize, &T)> {
+        self.get_min_entry()
+    }
+
+    /// Removes and returns the maximum value, preserving the order of the remaining elements.
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.pop_min()
+    }
+}
+
+impl<T, const S: usize, C: TotalCompare<T>> MinSmallVec<T, S, C> {
+    /// Sorts the elements using the comparator, preserving the order of equal elements.
+    /// The minimum is at index `0` afterwards, so no scan is needed with [TiePolicy::First].
+    pub fn sort(&mut self) {
+        let cmp = &self.order.cmp;
+        self.inner.sort_by(|a, b| cmp.total_compare(a, b));
+        self.update_sorted();
+    }
+
+    /// Sorts the elements using the comparator, without preserving the order of equal elements.
+    /// The minimum is at index `0` afterwards, so no scan is needed with [TiePolicy::First].
+    pub fn sort_unstable(&mut self) {
+        let cmp = &self.order.cmp;
+        self.inner.sort_unstable_by(|a, b| cmp.total_compare(a, b));
+        self.update_sorted();
+    }
+
+    /// Updates the min value after sorting, when all equal minima are at the front.
+    fn update_sorted(&mut self) {
+        // sorting does not change the number of equal minima
+        self.min.runner_up = RunnerUp::Unknown;
+        self.min.state = match self.order.tie {
+            _ if self.inner.is_empty() => MinState::Empty,
+            TiePolicy::First => MinState::Min(0),
+            TiePolicy::Last => MinState::Min(self.min.count - 1),
+        };
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> Default for MinSmallVec<T, S, C> {
     fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: Clone, const S: usize, C: Clone> Clone for MinSmallVec<T, S, C> {
+    fn clone(&self) -> Self {
+        // the min is stored as an index, so it is equally valid for the cloned buffer
         Self {
-            inner: SmallVec::default(),
-            min: None,
+            inner: self.inner.clone(),
+            min: self.min,
+            order: self.order.clone(),
         }
     }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.min = source.min;
+        self.order.clone_from(&source.order);
+    }
 }
 
-impl<T: PartialOrd + Eq, const S: usize> PartialOrd for MinSmallVec<T, S> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.get_min()
-            .zip(other.get_min())
-            .and_then(|(s, o)| s.partial_cmp(o))
+/// Compares the minimum values using the comparator of `self`.
+///
+/// An empty collection is greater than any non-empty one and equal to another empty one.
+/// Collections whose min value is [MinState::Incomparable] can not be compared.
+impl<T, const S: usize, C: Compare<T>> PartialOrd for MinSmallVec<T, S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.min.state, other.min.state) {
+            (MinState::Min(s), MinState::Min(o)) => {
+                self.order.cmp.compare(&self.inner[s], &other.inner[o])
+            }
+            (MinState::Empty, MinState::Empty) => Some(Ordering::Equal),
+            (MinState::Empty, MinState::Min(_)) => Some(Ordering::Greater),
+            (MinState::Min(_), MinState::Empty) => Some(Ordering::Less),
+            _ => None,
+        }
     }
 }
 
-impl<T: PartialOrd + Eq, const S: usize> PartialEq for MinSmallVec<T, S> {
+/// Compares the minimum values using the comparator of `self`, consistent with [PartialOrd].
+impl<T, const S: usize, C: Compare<T>> PartialEq for MinSmallVec<T, S, C> {
     fn eq(&self, other: &Self) -> bool {
-        self.get_min().eq(&other.get_min())
+        self.partial_cmp(other) == Some(Ordering::Equal)
     }
 }
 
-impl<T: PartialOrd + Eq, const S: usize> Eq for MinSmallVec<T, S> {}
-
-impl<T: PartialOrd + Eq, const S: usize> FromIterator<T> for MinSmallVec<T, S> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let inner = SmallVec::from_iter(iter);
+impl<T, const S: usize, C: TotalCompare<T>> Eq for MinSmallVec<T, S, C> {}
 
-        Self {
-            min: slice_min(&inner),
-            inner,
+/// Compares the minimum values using the comparator of `self`,
+/// so that collections can be sorted or stored in a [BinaryHeap](std::collections::BinaryHeap).
+///
+/// An empty collection is greater than any non-empty one.
+impl<T, const S: usize, C: TotalCompare<T>> Ord for MinSmallVec<T, S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.min.state, other.min.state) {
+            (MinState::Min(s), MinState::Min(o)) => self
+                .order
+                .cmp
+                .total_compare(&self.inner[s], &other.inner[o]),
+            (MinState::Min(_), _) => Ordering::Less,
+            (_, MinState::Min(_)) => Ordering::Greater,
+            _ => Ordering::Equal,
         }
     }
 }
+
+impl<T, const S: usize, C> Deref for MinSmallVec<T, S, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T, const S: usize, C> AsRef<[T]> for MinSmallVec<T, S, C> {
+    fn as_ref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const S: usize, C> Index<I> for MinSmallVec<T, S, C> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.inner[index]
+    }
+}
+
+impl<T, const S: usize, C> IntoIterator for MinSmallVec<T, S, C> {
+    type Item = T;
+    type IntoIter = smallvec::IntoIter<[T; S]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T, const S: usize, C> IntoIterator for &'a MinSmallVec<T, S, C> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<'a, T, const S: usize, C: Compare<T>> IntoIterator for &'a mut MinSmallVec<T, S, C> {
+    type Item = ItemMut<'a, T, S, C>;
+    type IntoIter = IterMut<'a, T, S, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Extend<T> for MinSmallVec<T, S, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let len = self.inner.len();
+        self.inner.extend(iter);
+        self.update_inserted(len, self.inner.len() - len);
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> FromIterator<T> for MinSmallVec<T, S, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self {
+            inner: SmallVec::from_iter(iter),
+            min: MinTracker::EMPTY,
+            order: Order::new(C::default()),
+        };
+        vec.rescan();
+        vec
+    }
+}
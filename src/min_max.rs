@@ -0,0 +1,337 @@
+use std::{
+    ops::{Deref, Index, Range},
+    slice::SliceIndex,
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    tracker::{MinTracker, Order},
+    Compare, IncomparablePolicy, MinSmallVecError, MinState, Natural, TiePolicy,
+};
+
+/// A collection with a known minimum and maximum value backed by a [SmallVec].
+///
+/// Both values are maintained incrementally like the min value of a [MinSmallVec](crate::MinSmallVec).
+/// The [IncomparablePolicy] applies to the reversed order for the maximum, like with a
+/// [MaxSmallVec](crate::MaxSmallVec), so with [IncomparablePolicy::Greatest] an incomparable
+/// element is neither the minimum nor the maximum.
+///
+/// ```rust
+/// # use min_smallvec::MinMaxSmallVec;
+/// let mut vec = MinMaxSmallVec::<u32, 4>::new();
+/// vec.extend([3, 7, 5]);
+/// assert_eq!(vec.get_range(), Some((&3, &7)));
+/// vec.modify_single(1, |val| *val = 1);
+/// assert_eq!(vec.get_range(), Some((&1, &5)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinMaxSmallVec<T, const S: usize, C = Natural> {
+    inner: SmallVec<[T; S]>,
+    min: MinTracker,
+    /// The max value, which is tracked like the min value using the reversed order
+    max: MinTracker,
+    order: Order<C>,
+}
+
+impl<T: PartialOrd, const S: usize> MinMaxSmallVec<T, S> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates an empty collection treating incomparable elements according to `policy`.
+    pub fn with_policy(policy: IncomparablePolicy) -> Self {
+        let mut vec = Self::new();
+        vec.order.policy = policy;
+        vec
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> MinMaxSmallVec<T, S, C> {
+    /// Creates an empty collection whose minimum and maximum are determined by `cmp`.
+    pub fn with_comparator(cmp: C) -> Self {
+        Self {
+            inner: SmallVec::new(),
+            min: MinTracker::EMPTY,
+            max: MinTracker::EMPTY,
+            order: Order::new(cmp),
+        }
+    }
+
+    /// Get a reference to the comparator.
+    pub fn comparator(&self) -> &C {
+        &self.order.cmp
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns a slice of all elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+
+    /// Returns an iterator over references to all elements.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    /// Get a reference to the minimum value.
+    pub fn get_min(&self) -> Option<&T> {
+        self.min.state.index().map(|index| &self.inner[index])
+    }
+
+    /// Get the index of the minimum value.
+    pub fn get_min_index(&self) -> Option<usize> {
+        self.min.state.index()
+    }
+
+    /// Get a reference to the maximum value.
+    pub fn get_max(&self) -> Option<&T> {
+        self.max.state.index().map(|index| &self.inner[index])
+    }
+
+    /// Get the index of the maximum value.
+    pub fn get_max_index(&self) -> Option<usize> {
+        self.max.state.index()
+    }
+
+    /// Get references to the minimum and the maximum value.
+    ///
+    /// Returns [None] if either of them is missing.
+    pub fn get_range(&self) -> Option<(&T, &T)> {
+        self.get_min().zip(self.get_max())
+    }
+
+    /// Get the state of the minimum value.
+    pub fn min_state(&self) -> MinState {
+        self.min.state
+    }
+
+    /// Get the state of the maximum value.
+    pub fn max_state(&self) -> MinState {
+        self.max.state
+    }
+
+    /// Get the policy for incomparable elements.
+    pub fn policy(&self) -> IncomparablePolicy {
+        self.order.policy
+    }
+
+    /// Sets the policy for incomparable elements and recalculates both values using a linear scan.
+    pub fn set_policy(&mut self, policy: IncomparablePolicy) {
+        self.order.policy = policy;
+        self.rescan();
+    }
+
+    /// Get the policy deciding which of several equal elements is the min or max value.
+    pub fn tie_policy(&self) -> TiePolicy {
+        self.order.tie
+    }
+
+    /// Sets the policy deciding which of several equal elements is the min or max value
+    /// and recalculates both values using a linear scan.
+    pub fn set_tie_policy(&mut self, tie: TiePolicy) {
+        self.order.tie = tie;
+        self.rescan();
+    }
+
+    /// Recalculates both values using a linear scan.
+    fn rescan(&mut self) {
+        self.min = self.order.slice_min(&self.inner);
+        self.max = self.order.reversed().slice_min(&self.inner);
+    }
+
+    /// Applies a modification function to the elements and recalculates both values using a linear scan.
+    pub fn modify(&mut self, mut func: impl FnMut(&mut SmallVec<[T; S]>)) {
+        func(&mut self.inner);
+        self.rescan();
+    }
+
+    /// Modifies a single element. Each value is only recalculated using a linear scan
+    /// if the modified element was that value, otherwise the element is compared with it.
+    ///
+    /// Panics if the collection is empty or if `index` is out of bounds.
+    /// See [MinMaxSmallVec::try_modify_single] for a non-panicking version.
+    pub fn modify_single(&mut self, index: usize, func: impl FnMut(&mut T)) {
+        if let Err(err) = self.try_modify_single(index, func) {
+            panic!("{err}");
+        }
+    }
+
+    /// Modifies a single element like [MinMaxSmallVec::modify_single],
+    /// but returns an error instead of panicking. `func` is not called if an error is returned.
+    pub fn try_modify_single(
+        &mut self,
+        index: usize,
+        mut func: impl FnMut(&mut T),
+    ) -> Result<(), MinSmallVecError> {
+        let len = self.inner.len();
+
+        if len == 0 {
+            return Err(MinSmallVecError::Empty);
+        }
+
+        if index >= len {
+            return Err(MinSmallVecError::OutOfBounds { index, len });
+        }
+
+        let max_order = self.order.reversed();
+        let was_min = self.order.is_equal_to_min(&self.min, &self.inner, index);
+        let was_max = max_order.is_equal_to_min(&self.max, &self.inner, index);
+        func(&mut self.inner[index]);
+
+        self.order
+            .update_modified(&mut self.min, &self.inner, index, was_min);
+        max_order.update_modified(&mut self.max, &self.inner, index, was_max);
+
+        Ok(())
+    }
+
+    /// Pushes a value. Only the new value is compared with the minimum and the maximum.
+    pub fn push(&mut self, value: T) {
+        self.inner.push(value);
+        self.update_inserted(self.inner.len() - 1, 1);
+    }
+
+    /// Inserts a value at `index`, shifting all elements after it to the right.
+    /// Only the new value is compared with the minimum and the maximum.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.inner.insert(index, value);
+        self.update_inserted(index, 1);
+    }
+
+    /// Updates both values after `count` values have been inserted at `index`.
+    fn update_inserted(&mut self, index: usize, count: usize) {
+        self.order
+            .update_inserted(&mut self.min, &self.inner, index, count);
+        self.order
+            .reversed()
+            .update_inserted(&mut self.max, &self.inner, index, count);
+    }
+
+    /// Removes the last element and returns it, or [None] if the collection is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.inner.len().checked_sub(1)?;
+        Some(self.remove(index))
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// Each value is only recalculated if the removed element was that value.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.inner.len();
+        assert!(index < len, "index out of bounds");
+
+        self.update_removed(index..index + 1);
+        self.inner.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let value = self.inner.swap_remove(index);
+
+        self.order
+            .update_swap_removed(&mut self.min, &self.inner, index, &value);
+        self.order
+            .reversed()
+            .update_swap_removed(&mut self.max, &self.inner, index, &value);
+
+        value
+    }
+
+    /// Shortens the collection to `len` elements.
+    ///
+    /// Each value is only recalculated if it was one of the removed elements.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.inner.len() {
+            self.update_removed(len..self.inner.len());
+            self.inner.truncate(len);
+        }
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.min = MinTracker::EMPTY;
+        self.max = MinTracker::EMPTY;
+    }
+
+    /// Updates both values before the elements in `range` are removed.
+    fn update_removed(&mut self, range: Range<usize>) {
+        self.order
+            .update_removed(&mut self.min, &self.inner, range.clone());
+        self.order
+            .reversed()
+            .update_removed(&mut self.max, &self.inner, range);
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> Default for MinMaxSmallVec<T, S, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, const S: usize, C> Deref for MinMaxSmallVec<T, S, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const S: usize, C> Index<I> for MinMaxSmallVec<T, S, C> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.inner[index]
+    }
+}
+
+impl<T, const S: usize, C> IntoIterator for MinMaxSmallVec<T, S, C> {
+    type Item = T;
+    type IntoIter = smallvec::IntoIter<[T; S]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T, const S: usize, C> IntoIterator for &'a MinMaxSmallVec<T, S, C> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<T, const S: usize, C: Compare<T>> Extend<T> for MinMaxSmallVec<T, S, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let len = self.inner.len();
+        self.inner.extend(iter);
+        self.update_inserted(len, self.inner.len() - len);
+    }
+}
+
+impl<T, const S: usize, C: Compare<T> + Default> FromIterator<T> for MinMaxSmallVec<T, S, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::default();
+        vec.extend(iter);
+        vec
+    }
+}
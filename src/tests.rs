@@ -0,0 +1,671 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use crate::{
+    tracker::RunnerUp, Compare, IncomparablePolicy, LazyMinSmallVec, MaxSmallVec, MinBTree,
+    MinByKeySmallVec, MinMaxSmallVec, MinSmallVec, MinSmallVecError, MinState, MinTree, Natural,
+    TiePolicy, TotalCompare, TotalF32, TotalF64,
+};
+
+const POLICIES: [IncomparablePolicy; 4] = [
+    IncomparablePolicy::Poison,
+    IncomparablePolicy::Skip,
+    IncomparablePolicy::Greatest,
+    IncomparablePolicy::Least,
+];
+
+/// A xorshift generator, so that the randomized tests are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    /// Returns a small value, so that there are many equal elements, or sometimes NaN or `-0.0`.
+    fn value(&mut self) -> f64 {
+        match self.below(12) {
+            0 => f64::NAN,
+            1 => -0.0,
+            n => (n % 4) as f64,
+        }
+    }
+}
+
+/// Returns a collection with the same elements and settings as `vec`, whose min value has been
+/// calculated from scratch.
+fn rebuilt<C: Compare<f64> + Clone>(vec: &MinSmallVec<f64, 4, C>) -> MinSmallVec<f64, 4, C> {
+    let mut fresh = MinSmallVec::with_comparator(vec.comparator().clone());
+    fresh.set_policy(vec.policy());
+    fresh.set_tie_policy(vec.tie_policy());
+    fresh.set_runner_up_tracking(vec.runner_up_tracking());
+    fresh.extend(vec.iter().copied());
+    fresh
+}
+
+/// Applies a random mutation to `vec`.
+fn mutate<C: Compare<f64>>(vec: &mut MinSmallVec<f64, 4, C>, rng: &mut Rng) {
+    let len = vec.len();
+    let val = rng.value();
+
+    match rng.below(25) {
+        0 | 1 => vec.push(val),
+        2 => vec.insert(rng.below(len + 1), val),
+        3 => vec.insert_many(rng.below(len + 1), [val, 1.0]),
+        4 => vec.extend_from_slice(&[val, val]),
+        5 => {
+            vec.pop_min();
+        }
+        6 => {
+            vec.swap_remove_min();
+        }
+        7 => {
+            vec.pop();
+        }
+        8 => vec.truncate(rng.below(len + 1)),
+        9 => vec.retain(|elem| *elem != val),
+        10 => vec.dedup(),
+        11 => vec.reverse(),
+        12 => vec.extend([val, 2.0]),
+        13 => vec.retain_mut(|elem| {
+            *elem += 1.0;
+            *elem != val
+        }),
+        14 if rng.below(4) == 0 => vec.clear(),
+        15 => {
+            let start = rng.below(len + 1);
+            let end = start + rng.below(len + 1 - start);
+            // the iterator may be dropped before all removed elements have been yielded
+            vec.drain(start..end).next();
+        }
+        16 => {
+            let start = rng.below(len + 1);
+            let end = start + rng.below(len + 1 - start);
+            vec.splice(start..end, [val, 0.0]);
+        }
+        _ if len == 0 => {}
+        17 => {
+            vec.remove(rng.below(len));
+        }
+        18 => {
+            vec.swap_remove(rng.below(len));
+        }
+        19 => vec.modify_single(rng.below(len), |elem| *elem = val),
+        20 => vec.swap(rng.below(len), rng.below(len)),
+        21 => vec.rotate_left(rng.below(len)),
+        22 => vec.rotate_right(rng.below(len)),
+        23 => {
+            let indices = [rng.below(len), rng.below(len)];
+            vec.modify_many(indices, |elem| *elem = val);
+        }
+        _ => {
+            if let Some(mut min) = vec.min_mut() {
+                *min += val;
+            }
+        }
+    }
+
+    if vec.len() > 16 {
+        vec.truncate(4);
+    }
+}
+
+/// Applies a random mutation to `vec`, which may also sort it.
+fn mutate_total<C: TotalCompare<f64>>(vec: &mut MinSmallVec<f64, 4, C>, rng: &mut Rng) {
+    match rng.below(12) {
+        0 => vec.sort(),
+        1 => vec.sort_unstable(),
+        _ => mutate(vec, rng),
+    }
+}
+
+#[test]
+fn swap_remove_last_min() {
+    let mut vec = MinSmallVec::<u32, 4>::new();
+    vec.push(5);
+    vec.push(1);
+
+    assert_eq!(vec.swap_remove(1), 1);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    assert_eq!(vec.get_min(), Some(&5));
+}
+
+#[test]
+fn swap_remove_moves_last_min() {
+    let mut vec = MinSmallVec::<u32, 4>::new();
+    vec.extend([5, 3, 1]);
+
+    assert_eq!(vec.swap_remove(0), 5);
+    assert_eq!(vec.as_slice(), &[1, 3]);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+}
+
+#[test]
+fn skip_insert_nothing_keeps_incomparable() {
+    let mut vec = MinSmallVec::<f64, 4>::with_policy(IncomparablePolicy::Skip);
+    vec.push(f64::NAN);
+
+    vec.extend(std::iter::empty());
+    assert_eq!(vec.min_state(), MinState::Incomparable);
+    vec.insert_many(1, []);
+    assert_eq!(vec.min_state(), MinState::Incomparable);
+    vec.extend_from_slice(&[]);
+    assert_eq!(vec.min_state(), MinState::Incomparable);
+    vec.splice(1.., []);
+    assert_eq!(vec.min_state(), MinState::Incomparable);
+
+    let mut vec = MinMaxSmallVec::<f64, 4>::with_policy(IncomparablePolicy::Skip);
+    vec.push(f64::NAN);
+    vec.extend(std::iter::empty());
+    assert_eq!(vec.min_state(), MinState::Incomparable);
+    assert_eq!(vec.max_state(), MinState::Incomparable);
+}
+
+#[test]
+fn total_f64_order() {
+    let mut vec = MinSmallVec::<f64, 4, TotalF64>::default();
+    vec.extend([f64::NAN, 0.0, 1.0, -f64::NAN, -0.0, f64::NEG_INFINITY]);
+
+    let min = vec.pop_min().unwrap();
+    assert!(min.is_nan() && min.is_sign_negative());
+    assert_eq!(vec.pop_min(), Some(f64::NEG_INFINITY));
+    let min = vec.pop_min().unwrap();
+    assert!(min == 0.0 && min.is_sign_negative());
+    let min = vec.pop_min().unwrap();
+    assert!(min == 0.0 && min.is_sign_positive());
+    assert_eq!(vec.pop_min(), Some(1.0));
+    let min = vec.pop_min().unwrap();
+    assert!(min.is_nan() && min.is_sign_positive());
+    assert_eq!(vec.pop_min(), None);
+}
+
+#[test]
+fn total_f32_order() {
+    let mut vec = MinSmallVec::<f32, 4, TotalF32>::default();
+    vec.extend([f32::NAN, 0.0, 1.0, -f32::NAN, -0.0, f32::NEG_INFINITY]);
+
+    let min = vec.pop_min().unwrap();
+    assert!(min.is_nan() && min.is_sign_negative());
+    assert_eq!(vec.pop_min(), Some(f32::NEG_INFINITY));
+    let min = vec.pop_min().unwrap();
+    assert!(min == 0.0 && min.is_sign_negative());
+    let min = vec.pop_min().unwrap();
+    assert!(min == 0.0 && min.is_sign_positive());
+    assert_eq!(vec.pop_min(), Some(1.0));
+    let min = vec.pop_min().unwrap();
+    assert!(min.is_nan() && min.is_sign_positive());
+    assert_eq!(vec.pop_min(), None);
+}
+
+#[test]
+fn max_policy_matches() {
+    for policy in [IncomparablePolicy::Greatest, IncomparablePolicy::Least] {
+        let mut max = MaxSmallVec::<f64, 4>::default();
+        max.set_policy(policy);
+        max.extend([1.0, f64::NAN, 3.0]);
+        let mut min_max = MinMaxSmallVec::<f64, 4>::with_policy(policy);
+        min_max.extend([1.0, f64::NAN, 3.0]);
+
+        assert_eq!(min_max.get_max_index(), max.get_max_index());
+    }
+}
+
+#[test]
+fn permute_relocates_min() {
+    let mut vec = MinSmallVec::<u32, 4>::new();
+    vec.extend([4, 1, 3, 2, 5]);
+
+    vec.reverse();
+    assert_eq!(vec.min_state(), MinState::Min(3));
+    vec.rotate_left(2);
+    assert_eq!(vec.min_state(), MinState::Min(1));
+    vec.rotate_right(4);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    vec.swap(0, 4);
+    assert_eq!(vec.min_state(), MinState::Min(4));
+
+    vec.push(1);
+    vec.reverse();
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    vec.swap(0, 2);
+    assert_eq!(vec.min_state(), MinState::Min(1));
+}
+
+/// Asserts that the min state and count match a rescan after every mutation by `mutate`,
+/// for every combination of settings.
+fn assert_matches_rescan<C: Compare<f64> + Clone>(
+    cmp: C,
+    mutate: impl Fn(&mut MinSmallVec<f64, 4, C>, &mut Rng),
+) {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for policy in POLICIES {
+        for tie in [TiePolicy::First, TiePolicy::Last] {
+            for runner_up in [false, true] {
+                let mut vec = MinSmallVec::with_comparator(cmp.clone());
+                vec.set_policy(policy);
+                vec.set_tie_policy(tie);
+                vec.set_runner_up_tracking(runner_up);
+
+                for _ in 0..2000 {
+                    mutate(&mut vec, &mut rng);
+
+                    let fresh = rebuilt(&vec);
+                    assert_eq!(vec.min_state(), fresh.min_state(), "{:?}", vec.as_slice());
+                    assert_eq!(vec.min_count(), fresh.min_count(), "{:?}", vec.as_slice());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_rescan() {
+    assert_matches_rescan(Natural, mutate);
+}
+
+#[test]
+fn total_matches_rescan() {
+    assert_matches_rescan(TotalF64, mutate_total);
+}
+
+#[test]
+fn total_dedup_signed_zero() {
+    let mut vec = MinSmallVec::<f64, 4, TotalF64>::default();
+    vec.extend([0.0, -0.0]);
+
+    vec.dedup();
+    assert_eq!(vec.len(), 2);
+    assert_eq!(vec.min_state(), MinState::Min(1));
+    assert_eq!(vec.min_count(), 1);
+    assert_eq!(vec.pop_min(), Some(-0.0));
+    assert_eq!(vec.min_count(), 1);
+
+    // equal according to the comparator, so only the first one is kept
+    let mut vec = MinSmallVec::<f64, 4, TotalF64>::default();
+    vec.extend([1.0, f64::NAN, f64::NAN, 1.0]);
+    vec.dedup();
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+}
+
+#[test]
+fn runner_up_matches_rescan() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for policy in POLICIES {
+        for tie in [TiePolicy::First, TiePolicy::Last] {
+            let mut vec = MinSmallVec::<f64, 4>::with_policy(policy);
+            vec.set_tie_policy(tie);
+            vec.set_runner_up_tracking(true);
+
+            for _ in 0..2000 {
+                mutate(&mut vec, &mut rng);
+
+                // an unknown runner-up is calculated when it is needed
+                if vec.min.runner_up != RunnerUp::Unknown {
+                    let fresh = rebuilt(&vec);
+                    assert_eq!(
+                        vec.min.runner_up,
+                        fresh.min.runner_up,
+                        "{:?}",
+                        vec.as_slice()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pop_min_promotes_runner_up() {
+    let mut vec = MinSmallVec::<u32, 4>::new();
+    vec.set_runner_up_tracking(true);
+    vec.extend([3, 1, 2, 2]);
+    assert_eq!(vec.min.runner_up, RunnerUp::Known(2, 2));
+
+    assert_eq!(vec.pop_min(), Some(1));
+    assert_eq!(vec.min_state(), MinState::Min(1));
+    assert_eq!(vec.min_count(), 2);
+}
+
+#[test]
+fn lazy_modify_scans_new_elements() {
+    let mut vec = LazyMinSmallVec::<u32, 4>::new();
+    vec.modify(|inner| {
+        inner.push(5);
+        inner.push(1);
+    });
+    assert_eq!(vec.get_min(), Some(&1));
+
+    let mut vec = LazyMinSmallVec::<u32, 4>::new();
+    vec.extend([5, 6]);
+    assert_eq!(vec.get_min(), Some(&5));
+    vec.modify(|inner| inner.push(1));
+    assert_eq!(vec.get_min(), Some(&1));
+    vec.modify(|inner| inner.truncate(1));
+    assert_eq!(vec.get_min(), Some(&5));
+}
+
+#[test]
+fn btree_nodes_stay_inline() {
+    let mut rng = Rng(0x5851_f42d_4c95_7f2d);
+    let mut tree = MinBTree::<u32, 4>::new();
+
+    for _ in 0..2000 {
+        let len = tree.len();
+        if len > 0 && rng.below(3) == 0 {
+            tree.remove(rng.below(len));
+        } else {
+            tree.insert(rng.below(len + 1), rng.below(100) as u32);
+        }
+        assert!(!tree.spilled());
+    }
+}
+
+/// Asserts that the min state and count of `vec` match a rescan.
+fn assert_rescanned(vec: &MinSmallVec<f64, 4>) {
+    let fresh = rebuilt(vec);
+    assert_eq!(vec.min_state(), fresh.min_state(), "{:?}", vec.as_slice());
+    assert_eq!(vec.min_count(), fresh.min_count(), "{:?}", vec.as_slice());
+}
+
+#[test]
+fn get_mut_updates_min() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.extend([3.0, 1.0, 2.0, 1.0]);
+
+    *vec.get_mut(2).unwrap() = 0.0;
+    assert_eq!(vec.min_state(), MinState::Min(2));
+    *vec.get_mut(2).unwrap() = 5.0;
+    assert_eq!(vec.min_state(), MinState::Min(1));
+    assert_eq!(vec.min_count(), 2);
+    *vec.get_mut(3).unwrap() = 4.0;
+    assert_eq!(vec.min_count(), 1);
+
+    let elem = vec.get_mut(1).unwrap();
+    assert_eq!(*elem, 1.0);
+    drop(elem);
+    assert_eq!(vec.min_state(), MinState::Min(1));
+    assert!(vec.get_mut(4).is_none());
+    assert_rescanned(&vec);
+}
+
+#[test]
+fn iter_mut_untouched() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.set_runner_up_tracking(true);
+    vec.extend([3.0, 1.0, 2.0, 1.0, 2.0]);
+    let min = vec.min;
+
+    let sum: f64 = vec.iter_mut().map(|item| *item).sum();
+    assert_eq!(sum, 9.0);
+    assert_eq!(vec.min, min);
+}
+
+#[test]
+fn iter_mut_guards_dropped_out_of_order() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.extend([5.0, 3.0, 4.0, 3.0, 6.0, 7.0]);
+
+    let mut items: Vec<_> = vec.iter_mut().collect();
+    *items[4] = 2.0;
+    *items[3] = 8.0;
+    *items[5] = 2.0;
+    *items[0] = 1.0;
+    for i in [2, 5, 0, 4, 1, 3] {
+        let item = items.remove(items.iter().position(|item| item.index() == i).unwrap());
+        drop(item);
+    }
+    drop(items);
+
+    assert_eq!(vec.as_slice(), &[1.0, 3.0, 4.0, 8.0, 2.0, 2.0]);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    assert_rescanned(&vec);
+}
+
+#[test]
+fn iter_mut_min_modified_while_others_alive() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.extend([2.0, 1.0, 1.0, 3.0]);
+
+    // the guard of the minimum is alive while an element equal to it is modified
+    let mut items: Vec<_> = vec.iter_mut().collect();
+    *items[2] = 5.0;
+    *items[1] = 9.0;
+    drop(items);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    assert_rescanned(&vec);
+
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.extend([1.0, 1.0, 3.0]);
+
+    let mut items: Vec<_> = vec.iter_mut().collect();
+    *items[1] = 7.0;
+    drop(items);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    assert_eq!(vec.min_count(), 1);
+    assert_rescanned(&vec);
+}
+
+#[test]
+fn iter_mut_next_back() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    vec.extend([4.0, 2.0, 3.0, 2.0]);
+
+    let mut iter = vec.iter_mut();
+    let mut last = iter.next_back().unwrap();
+    assert_eq!(last.index(), 3);
+    *last = 1.0;
+    let mut first = iter.next().unwrap();
+    *first = 1.0;
+    assert_eq!(iter.len(), 2);
+    for mut item in iter.rev() {
+        *item += 10.0;
+    }
+    drop(first);
+    drop(last);
+
+    assert_eq!(vec.as_slice(), &[1.0, 12.0, 13.0, 1.0]);
+    assert_eq!(vec.min_state(), MinState::Min(0));
+    assert_eq!(vec.min_count(), 2);
+    assert_rescanned(&vec);
+}
+
+#[test]
+fn ord_compares_min_values() {
+    let buckets = [vec![3, 8], vec![], vec![1, 9], vec![5]];
+    let buckets = buckets.map(|values| values.into_iter().collect::<MinSmallVec<u32, 4>>());
+
+    let mut sorted = buckets.to_vec();
+    sorted.sort();
+    let mins: Vec<_> = sorted.iter().map(|vec| vec.get_min().copied()).collect();
+    // an empty collection is greater than any non-empty one
+    assert_eq!(mins, [Some(1), Some(3), Some(5), None]);
+
+    let mut heap: BinaryHeap<_> = buckets.into_iter().map(Reverse).collect();
+    let mins: Vec<_> = std::iter::from_fn(|| heap.pop())
+        .map(|Reverse(vec)| vec.get_min().copied())
+        .collect();
+    assert_eq!(mins, [Some(1), Some(3), Some(5), None]);
+
+    assert_eq!(
+        MinSmallVec::<u32, 4>::new().cmp(&MinSmallVec::new()),
+        Ordering::Equal
+    );
+    let nan = MinSmallVec::<f64, 4>::from_slice(&[f64::NAN]);
+    assert_eq!(nan.partial_cmp(&MinSmallVec::from_slice(&[1.0])), None);
+}
+
+#[test]
+fn leaked_drain_leaves_no_min() {
+    let mut vec = MinSmallVec::<u32, 4>::new();
+    vec.extend([5, 6, 7, 1, 8]);
+
+    std::mem::forget(vec.drain(1..));
+    assert_eq!(vec.len(), 1);
+    assert_eq!(vec.min_state(), MinState::Empty);
+    assert_eq!(vec.get_min(), None);
+
+    vec.push(9);
+    assert_eq!(vec.get_min(), Some(&5));
+}
+
+#[test]
+fn tree_matches_model() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+    let mut tree = MinTree::<u32, 4, 3>::new();
+    // the buckets of the tree, which are never empty
+    let mut model: Vec<Vec<u32>> = Vec::new();
+
+    for _ in 0..5000 {
+        let val = rng.below(20) as u32;
+
+        match rng.below(5) {
+            0 | 1 => {
+                let path = tree.push(val);
+                match model.last_mut().filter(|bucket| bucket.len() < 3) {
+                    Some(bucket) => bucket.push(val),
+                    None => model.push(vec![val]),
+                }
+                assert_eq!(path, (model.len() - 1, model.last().unwrap().len() - 1));
+            }
+            _ if model.is_empty() => {}
+            2 => {
+                let outer = rng.below(model.len());
+                let inner = rng.below(model[outer].len());
+                tree.modify((outer, inner), |elem| *elem = val);
+                model[outer][inner] = val;
+            }
+            3 => {
+                let outer = rng.below(model.len());
+                let inner = rng.below(model[outer].len());
+                assert_eq!(tree.remove((outer, inner)), model[outer].remove(inner));
+                // the following buckets are shifted to the left
+                if model[outer].is_empty() {
+                    model.remove(outer);
+                }
+            }
+            _ => {
+                let (outer, inner) = tree.locate_min().unwrap();
+                assert_eq!(tree.pop_min(), Some(model[outer].remove(inner)));
+                if model[outer].is_empty() {
+                    model.remove(outer);
+                }
+            }
+        }
+
+        // the first smallest element in the order of the paths
+        let min = (0..model.len())
+            .flat_map(|outer| (0..model[outer].len()).map(move |inner| (outer, inner)))
+            .min_by_key(|&(outer, inner)| model[outer][inner]);
+        assert_eq!(tree.locate_min(), min);
+        assert_eq!(
+            tree.get_min(),
+            min.map(|(outer, inner)| &model[outer][inner])
+        );
+        assert_eq!(tree.len(), model.iter().map(Vec::len).sum::<usize>());
+        let buckets: Vec<_> = tree
+            .buckets()
+            .iter()
+            .map(|bucket| bucket.to_vec())
+            .collect();
+        assert_eq!(buckets, model);
+    }
+}
+
+#[test]
+fn by_key_modify_single_recomputes_key() {
+    let mut vec = MinByKeySmallVec::<String, 4, usize, _>::new(|s: &String| s.len());
+    vec.extend(["apple", "fig", "banana"].map(String::from));
+    assert_eq!(vec.get_min_index(), Some(1));
+
+    vec.modify_single(2, |s| s.truncate(1));
+    assert_eq!(vec.keys(), &[5, 3, 1]);
+    assert_eq!(vec.get_min(), Some(&String::from("b")));
+    vec.modify_single(2, |s| s.push_str("lueberry"));
+    assert_eq!(vec.get_min_key(), Some(&3));
+    assert_eq!(vec.get_min_index(), Some(1));
+}
+
+#[test]
+fn by_key_removal_keeps_keys_aligned() {
+    let mut vec = MinByKeySmallVec::<u32, 4, u32, _>::new(|val: &u32| val % 10);
+    vec.extend([13, 21, 35, 42, 50]);
+
+    assert_eq!(vec.swap_remove(0), 13);
+    assert_eq!(vec.as_slice(), &[50, 21, 35, 42]);
+    assert_eq!(vec.keys(), &[0, 1, 5, 2]);
+    assert_eq!(vec.get_min(), Some(&50));
+
+    assert_eq!(vec.pop_min(), Some(50));
+    assert_eq!(vec.as_slice(), &[21, 35, 42]);
+    assert_eq!(vec.keys(), &[1, 5, 2]);
+    assert_eq!(vec.pop_min(), Some(21));
+    assert_eq!(vec.keys(), &[5, 2]);
+    assert_eq!(vec.get_min_entry(), Some((1, &42)));
+}
+
+#[test]
+fn by_key_out_of_bounds_does_not_call_func() {
+    let mut vec = MinByKeySmallVec::<u32, 4, u32, _>::new(|val: &u32| *val);
+    vec.extend([3, 1]);
+
+    let mut called = false;
+    let result = vec.try_modify_single(2, |_| called = true);
+    assert_eq!(
+        result,
+        Err(MinSmallVecError::OutOfBounds { index: 2, len: 2 })
+    );
+    assert!(!called);
+    assert_eq!(vec.keys(), &[3, 1]);
+}
+
+#[test]
+fn by_key_policy_getters() {
+    let mut vec =
+        MinByKeySmallVec::<f64, 4, f64, _>::with_policy(|val: &f64| *val, IncomparablePolicy::Skip);
+    assert_eq!(vec.policy(), IncomparablePolicy::Skip);
+    assert_eq!(vec.tie_policy(), TiePolicy::First);
+
+    vec.set_tie_policy(TiePolicy::Last);
+    vec.set_policy(IncomparablePolicy::Greatest);
+    assert_eq!(vec.tie_policy(), TiePolicy::Last);
+    assert_eq!(vec.policy(), IncomparablePolicy::Greatest);
+}
+
+#[test]
+fn try_methods_report_errors() {
+    let mut vec = MinSmallVec::<f64, 4>::new();
+    assert_eq!(vec.try_get_min(), Err(MinSmallVecError::Empty));
+    assert_eq!(vec.try_get_min_entry(), Err(MinSmallVecError::Empty));
+    assert_eq!(vec.try_pop_min(), Err(MinSmallVecError::Empty));
+    assert_eq!(
+        vec.try_modify_single(0, |_| unreachable!()),
+        Err(MinSmallVecError::Empty)
+    );
+
+    vec.extend([2.0, f64::NAN, 1.0]);
+    assert_eq!(vec.try_get_min(), Err(MinSmallVecError::Incomparable));
+    assert_eq!(vec.try_get_min_entry(), Err(MinSmallVecError::Incomparable));
+    assert_eq!(vec.try_pop_min(), Err(MinSmallVecError::Incomparable));
+    assert_eq!(vec.len(), 3);
+    assert_eq!(
+        vec.try_modify_single(3, |_| unreachable!()),
+        Err(MinSmallVecError::OutOfBounds { index: 3, len: 3 })
+    );
+
+    assert_eq!(vec.try_modify_single(1, |val| *val = 0.0), Ok(()));
+    assert_eq!(vec.try_get_min_entry(), Ok((1, &0.0)));
+    assert_eq!(vec.try_pop_min(), Ok(0.0));
+    assert_eq!(vec.try_get_min(), Ok(&1.0));
+
+    let mut vec = MinSmallVec::<f64, 4>::with_policy(IncomparablePolicy::Skip);
+    vec.push(f64::NAN);
+    assert_eq!(vec.try_get_min(), Err(MinSmallVecError::Incomparable));
+}
@@ -0,0 +1,686 @@
+use std::{cmp::Ordering, ops::Range};
+
+use crate::{Compare, IncomparablePolicy, MinState, Reversed, TiePolicy};
+
+/// Delegates to a borrowed comparator.
+pub(crate) struct ByRef<'a, C>(&'a C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for ByRef<'_, C> {
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        self.0.compare(a, b)
+    }
+}
+
+/// The comparator and policies used to calculate the min value.
+#[derive(Debug, Clone)]
+pub(crate) struct Order<C> {
+    pub(crate) cmp: C,
+    pub(crate) policy: IncomparablePolicy,
+    pub(crate) tie: TiePolicy,
+    /// Whether scans also calculate the [RunnerUp]
+    pub(crate) track_runner_up: bool,
+}
+
+impl<C> Order<C> {
+    pub(crate) fn new(cmp: C) -> Self {
+        Self {
+            cmp,
+            policy: IncomparablePolicy::default(),
+            tie: TiePolicy::default(),
+            track_runner_up: false,
+        }
+    }
+
+    /// Returns the index preferred by the [TiePolicy] out of two equal elements at `lhs` and `rhs`.
+    pub(crate) fn tie_break(&self, lhs: usize, rhs: usize) -> usize {
+        match self.tie {
+            TiePolicy::First => lhs.min(rhs),
+            TiePolicy::Last => lhs.max(rhs),
+        }
+    }
+
+    /// Returns the order used to calculate the max value,
+    /// which applies the [IncomparablePolicy] to the reversed order like [Reversed].
+    pub(crate) fn reversed(&self) -> Order<Reversed<ByRef<'_, C>>> {
+        Order {
+            cmp: Reversed(ByRef(&self.cmp)),
+            policy: self.policy,
+            tie: self.tie,
+            track_runner_up: self.track_runner_up,
+        }
+    }
+
+    /// Compares `a` with `b`, only returning [None] with [IncomparablePolicy::Poison].
+    pub(crate) fn compare<T>(&self, a: &T, b: &T) -> Option<Ordering>
+    where
+        C: Compare<T>,
+    {
+        let ord = self.cmp.compare(a, b);
+
+        if ord.is_some() || self.policy == IncomparablePolicy::Poison {
+            return ord;
+        }
+
+        let ord = match (self.is_incomparable(a), self.is_incomparable(b)) {
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => Ordering::Equal,
+        };
+
+        Some(match self.policy {
+            IncomparablePolicy::Least => ord.reverse(),
+            _ => ord,
+        })
+    }
+
+    /// Returns `true` if `val` can not be compared with itself.
+    fn is_incomparable<T>(&self, val: &T) -> bool
+    where
+        C: Compare<T>,
+    {
+        self.cmp.compare(val, val).is_none()
+    }
+
+    /// Returns the state of the min value at `index`, whose value is `min`.
+    pub(crate) fn min_state<T>(&self, index: usize, min: &T) -> MinState
+    where
+        C: Compare<T>,
+    {
+        if self.policy == IncomparablePolicy::Skip && self.is_incomparable(min) {
+            MinState::Incomparable
+        } else {
+            MinState::Min(index)
+        }
+    }
+
+    /// Returns `true` if `a` and `b` compare equal.
+    pub(crate) fn is_equal<T>(&self, a: &T, b: &T) -> bool
+    where
+        C: Compare<T>,
+    {
+        self.compare(a, b) == Some(Ordering::Equal)
+    }
+
+    /// Returns the min value of `slice`.
+    pub(crate) fn slice_min<T>(&self, slice: &[T]) -> MinTracker
+    where
+        C: Compare<T>,
+    {
+        self.slice_min_in(slice.iter().enumerate())
+    }
+
+    /// Returns the min value of the elements yielded by `iter`,
+    /// which has to yield `(index, value)` pairs in ascending index order.
+    pub(crate) fn slice_min_in<'a, T: 'a>(
+        &self,
+        mut iter: impl Iterator<Item = (usize, &'a T)>,
+    ) -> MinTracker
+    where
+        C: Compare<T>,
+    {
+        let Some(mut min) = iter.next() else {
+            return MinTracker::EMPTY;
+        };
+        let mut count = 1;
+        // [None] if the runner-up is not calculated, otherwise the best other element and its count
+        let mut runner_up: Option<Option<((usize, &T), usize)>> =
+            self.track_runner_up.then_some(None);
+
+        for val in iter {
+            // the element which is not the min value afterwards
+            let other = match self.compare(min.1, val.1) {
+                Some(Ordering::Greater) => {
+                    count = 1;
+                    std::mem::replace(&mut min, val)
+                }
+                Some(Ordering::Equal) => {
+                    count += 1;
+                    match self.tie {
+                        TiePolicy::First => val,
+                        TiePolicy::Last => std::mem::replace(&mut min, val),
+                    }
+                }
+                Some(Ordering::Less) => val,
+                None => return MinTracker::INCOMPARABLE,
+            };
+
+            runner_up = runner_up.and_then(|runner_up| match runner_up {
+                None => Some(Some((other, 1))),
+                Some((best, best_count)) => match self.compare(best.1, other.1)? {
+                    Ordering::Greater => Some(Some((other, 1))),
+                    Ordering::Equal if self.tie_break(best.0, other.0) == other.0 => {
+                        Some(Some((other, best_count + 1)))
+                    }
+                    Ordering::Equal => Some(Some((best, best_count + 1))),
+                    Ordering::Less => Some(Some((best, best_count))),
+                },
+            });
+        }
+
+        let MinState::Min(index) = self.min_state(min.0, min.1) else {
+            return MinTracker::INCOMPARABLE;
+        };
+        let runner_up = match runner_up {
+            None => RunnerUp::Unknown,
+            Some(Some(((index, val), count)))
+                if self.min_state(index, val) != MinState::Incomparable =>
+            {
+                RunnerUp::Known(index, count)
+            }
+            Some(_) => RunnerUp::Absent,
+        };
+
+        MinTracker {
+            state: MinState::Min(index),
+            count,
+            runner_up,
+        }
+    }
+
+    /// Returns the min value of the single element of `slice` at `index`.
+    fn single<T>(&self, slice: &[T], index: usize) -> MinTracker
+    where
+        C: Compare<T>,
+    {
+        match self.min_state(index, &slice[index]) {
+            MinState::Min(index) if self.track_runner_up => MinTracker {
+                runner_up: RunnerUp::Absent,
+                ..MinTracker::min(index, 1)
+            },
+            MinState::Min(index) => MinTracker::min(index, 1),
+            _ => MinTracker::INCOMPARABLE,
+        }
+    }
+
+    /// Returns the min value of two disjoint sets of elements of `slice`,
+    /// given the min value of each set.
+    pub(crate) fn combine<T>(&self, slice: &[T], lhs: MinTracker, rhs: MinTracker) -> MinTracker
+    where
+        C: Compare<T>,
+    {
+        match (lhs.state, rhs.state) {
+            (MinState::Min(l), MinState::Min(r)) => {
+                let (min, other, count) = match self.compare(&slice[l], &slice[r]) {
+                    Some(Ordering::Less) => (lhs, rhs, lhs.count),
+                    Some(Ordering::Greater) => (rhs, lhs, rhs.count),
+                    Some(Ordering::Equal) if self.tie_break(l, r) == l => {
+                        (lhs, rhs, lhs.count + rhs.count)
+                    }
+                    Some(Ordering::Equal) => (rhs, lhs, lhs.count + rhs.count),
+                    None => return MinTracker::INCOMPARABLE,
+                };
+
+                MinTracker {
+                    state: min.state,
+                    count,
+                    runner_up: self.combine_runner_up(slice, min.runner_up, other),
+                }
+            }
+            (MinState::Empty, _) => rhs,
+            (_, MinState::Empty) => lhs,
+            // with `Skip`, a set without comparable elements does not affect the other one
+            (MinState::Incomparable, _) if self.policy == IncomparablePolicy::Skip => rhs,
+            (_, MinState::Incomparable) if self.policy == IncomparablePolicy::Skip => lhs,
+            _ => MinTracker::INCOMPARABLE,
+        }
+    }
+
+    /// Returns the runner-up after the elements of `other`, which do not contain the min value,
+    /// have been added to the set whose runner-up is `runner_up`.
+    fn combine_runner_up<T>(&self, slice: &[T], runner_up: RunnerUp, other: MinTracker) -> RunnerUp
+    where
+        C: Compare<T>,
+    {
+        let MinState::Min(index) = other.state else {
+            return runner_up;
+        };
+
+        match runner_up {
+            _ if !self.track_runner_up => RunnerUp::Unknown,
+            RunnerUp::Unknown => RunnerUp::Unknown,
+            RunnerUp::Absent => RunnerUp::Known(index, other.count),
+            RunnerUp::Known(r, count) => match self.compare(&slice[r], &slice[index]) {
+                Some(Ordering::Less) => runner_up,
+                Some(Ordering::Greater) => RunnerUp::Known(index, other.count),
+                Some(Ordering::Equal) => {
+                    RunnerUp::Known(self.tie_break(r, index), count + other.count)
+                }
+                None => RunnerUp::Unknown,
+            },
+        }
+    }
+
+    /// Updates `min` with the element of `slice` at `index`, which is not yet part of it.
+    fn merge<T>(&self, min: &mut MinTracker, slice: &[T], index: usize)
+    where
+        C: Compare<T>,
+    {
+        *min = self.combine(slice, *min, self.single(slice, index));
+    }
+
+    /// Returns the first index yielded by `iter` whose value is equal to `min`.
+    fn find_equal<'a, T: 'a>(
+        &self,
+        min: &T,
+        mut iter: impl Iterator<Item = (usize, &'a T)>,
+    ) -> Option<usize>
+    where
+        C: Compare<T>,
+    {
+        iter.find(|(_, val)| self.is_equal(min, val))
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the number of elements of `slice` in `range` equal to the element at `index`,
+    /// of which there are `count` in total.
+    fn count_equal<T>(&self, slice: &[T], index: usize, count: usize, range: Range<usize>) -> usize
+    where
+        C: Compare<T>,
+    {
+        if count > 1 {
+            range
+                .filter(|&i| i == index || self.is_equal(&slice[index], &slice[i]))
+                .count()
+        } else {
+            usize::from(range.contains(&index))
+        }
+    }
+
+    /// Returns `true` if the element of `slice` at `index` is not the min value but equal to it.
+    ///
+    /// Only compares the elements if the min value is known to have duplicates.
+    pub(crate) fn is_equal_to_min<T>(&self, min: &MinTracker, slice: &[T], index: usize) -> bool
+    where
+        C: Compare<T>,
+    {
+        match min.state {
+            MinState::Min(min_index) if min_index != index && min.count > 1 => {
+                self.is_equal(&slice[min_index], &slice[index])
+            }
+            _ => false,
+        }
+    }
+
+    /// Updates `min` after `count` values have been inserted into `slice` at `index`
+    /// by comparing only the inserted values with the current minimum.
+    pub(crate) fn update_inserted<T>(
+        &self,
+        min: &mut MinTracker,
+        slice: &[T],
+        index: usize,
+        count: usize,
+    ) where
+        C: Compare<T>,
+    {
+        if count == 0 {
+            return;
+        }
+
+        let shift = |i: usize| if i >= index { i + count } else { i };
+
+        match min.state {
+            MinState::Min(min_index) => {
+                min.state = MinState::Min(shift(min_index));
+                if let RunnerUp::Known(r, r_count) = min.runner_up {
+                    min.runner_up = RunnerUp::Known(shift(r), r_count);
+                }
+
+                for i in index..index + count {
+                    self.merge(min, slice, i);
+                }
+            }
+            MinState::Empty => *min = self.slice_min(slice),
+            // no existing element is comparable, so only the inserted ones can be the minimum
+            MinState::Incomparable if self.policy == IncomparablePolicy::Skip => {
+                let inserted = slice[index..index + count].iter().enumerate();
+                *min = self.slice_min_in(inserted.map(|(i, val)| (index + i, val)));
+            }
+            // the incomparable element is still present
+            MinState::Incomparable => {}
+        }
+    }
+
+    /// Updates `min` after the element of `slice` at `index` has been modified.
+    ///
+    /// `was_equal` is the result of [Order::is_equal_to_min] before the modification.
+    pub(crate) fn update_modified<T>(
+        &self,
+        min: &mut MinTracker,
+        slice: &[T],
+        index: usize,
+        was_equal: bool,
+    ) where
+        C: Compare<T>,
+    {
+        match (min.state, min.runner_up) {
+            (MinState::Min(min_index), runner_up) if min_index != index => {
+                min.runner_up = match runner_up {
+                    RunnerUp::Known(r, _) if r == index => RunnerUp::Unknown,
+                    // the runner-up is equal to the min value
+                    RunnerUp::Known(r, count) if min.count > 1 => {
+                        RunnerUp::Known(r, count - usize::from(was_equal))
+                    }
+                    // the runner-up is the only element equal to itself
+                    RunnerUp::Known(_, 1) => runner_up,
+                    RunnerUp::Known(..) => RunnerUp::Unknown,
+                    _ => runner_up,
+                };
+                if was_equal {
+                    min.count -= 1;
+                }
+
+                self.merge(min, slice, index);
+            }
+            // the min value has been modified, so it only has to be compared with the runner-up
+            (MinState::Min(_), RunnerUp::Known(..) | RunnerUp::Absent) => {
+                let others = match min.runner_up {
+                    RunnerUp::Known(r, count) => MinTracker::min(r, count),
+                    _ => MinTracker::EMPTY,
+                };
+                *min = self.combine(slice, self.single(slice, index), others);
+            }
+            _ => *min = self.slice_min(slice),
+        }
+    }
+
+    /// Updates `min` after an element of `slice` has been moved to `index`
+    /// without changing the relative order of the other elements.
+    pub(crate) fn update_moved<T>(&self, min: &mut MinTracker, slice: &[T], index: usize)
+    where
+        C: Compare<T>,
+    {
+        let MinState::Min(min_index) = min.state else {
+            return;
+        };
+
+        if self.is_equal_to_min(min, slice, index) {
+            if self.tie_break(min_index, index) == index {
+                min.state = MinState::Min(index);
+                min.runner_up = RunnerUp::Unknown;
+                return;
+            }
+        } else if !matches!(min.runner_up, RunnerUp::Known(r, count)
+            if r != index && count > 1 && self.is_equal(&slice[r], &slice[index]))
+        {
+            return;
+        }
+
+        // the element is equal to the runner-up
+        if let RunnerUp::Known(r, count) = min.runner_up {
+            min.runner_up = RunnerUp::Known(self.tie_break(r, index), count);
+        }
+    }
+
+    /// Updates `min` before the elements of `slice` in `range` are removed.
+    ///
+    /// If the minimum is removed, it is replaced by the runner-up or an equal element if there is one,
+    /// and the remaining elements are only scanned otherwise.
+    pub(crate) fn update_removed<T>(&self, min: &mut MinTracker, slice: &[T], range: Range<usize>)
+    where
+        C: Compare<T>,
+    {
+        let Range { start, end } = range;
+        let shift = |i: usize| if i >= end { i - (end - start) } else { i };
+        let head = slice[..start].iter().enumerate();
+        let tail = slice[end..]
+            .iter()
+            .enumerate()
+            .map(|(i, val)| (start + i, val));
+
+        let MinState::Min(min_index) = min.state else {
+            *min = self.slice_min_in(head.chain(tail));
+            return;
+        };
+
+        let removed = self.count_equal(slice, min_index, min.count, range.clone());
+        let min_removed = range.contains(&min_index);
+
+        if let RunnerUp::Known(r, count) = min.runner_up {
+            min.runner_up = if range.contains(&r) {
+                RunnerUp::Unknown
+            } else {
+                // elements equal to the min value are equal to the runner-up if there are several
+                let removed = match min.count {
+                    1 => self.count_equal(slice, r, count, range.clone()),
+                    _ => removed - usize::from(min_removed),
+                };
+                RunnerUp::Known(shift(r), count - removed)
+            };
+        }
+        min.count -= removed;
+
+        if !min_removed {
+            min.state = MinState::Min(shift(min_index));
+            return;
+        }
+
+        if let RunnerUp::Known(r, count) = min.runner_up {
+            *min = MinTracker::min(r, count);
+            return;
+        }
+
+        // the minimum is the first or last of the equal elements, so an equal remaining
+        // element can only be on one side of the removed range
+        let equal = match self.tie {
+            _ if min.count == 0 => None,
+            TiePolicy::First => self.find_equal(&slice[min_index], tail),
+            TiePolicy::Last => self.find_equal(&slice[min_index], head.rev()),
+        };
+
+        *min = match equal {
+            Some(index) => MinTracker::min(index, min.count),
+            None => {
+                let head = slice[..start].iter().enumerate();
+                let tail = slice[end..].iter().enumerate();
+                self.slice_min_in(head.chain(tail.map(|(i, val)| (start + i, val))))
+            }
+        };
+    }
+
+    /// Updates `min` after the element at `index` has been replaced with the last element of `slice`,
+    /// which is the slice after the removal, and `removed` is the removed element.
+    pub(crate) fn update_swap_removed<T>(
+        &self,
+        min: &mut MinTracker,
+        slice: &[T],
+        index: usize,
+        removed: &T,
+    ) where
+        C: Compare<T>,
+    {
+        let MinState::Min(min_index) = min.state else {
+            *min = self.slice_min(slice);
+            return;
+        };
+        // the last element has been moved to `index`
+        let moved = |i: usize| if i == slice.len() { index } else { i };
+
+        if min_index == index {
+            min.count -= 1;
+
+            // the moved element only has to be compared with the promoted runner-up,
+            // unless it is the runner-up itself and has equal elements
+            if let RunnerUp::Known(r, count) = min.runner_up {
+                if moved(r) != index || count == 1 {
+                    *min = MinTracker::min(moved(r), count);
+                    if moved(r) != index && index < slice.len() {
+                        self.update_moved(min, slice, index);
+                    }
+                    return;
+                }
+            }
+
+            // the elements after `index` have been behind the minimum, and so has the moved one
+            let equal = match self.tie {
+                _ if min.count == 0 => None,
+                TiePolicy::First => {
+                    let tail = slice[index..].iter().enumerate();
+                    self.find_equal(removed, tail.map(|(i, val)| (index + i, val)))
+                }
+                TiePolicy::Last => {
+                    self.find_equal(removed, slice[..index].iter().enumerate().rev())
+                }
+            };
+
+            *min = match equal {
+                Some(index) => MinTracker::min(index, min.count),
+                None => self.slice_min(slice),
+            };
+            return;
+        }
+
+        let min_index = moved(min_index);
+        min.state = MinState::Min(min_index);
+
+        if min.count > 1 && self.is_equal(&slice[min_index], removed) {
+            min.count -= 1;
+        }
+
+        min.runner_up = match min.runner_up {
+            RunnerUp::Known(r, _) if r == index => RunnerUp::Unknown,
+            RunnerUp::Known(r, 1) => RunnerUp::Known(moved(r), 1),
+            // the elements equal to the runner-up have been reordered if it has been moved
+            RunnerUp::Known(r, count) if r != slice.len() => {
+                RunnerUp::Known(r, count - usize::from(self.is_equal(&slice[r], removed)))
+            }
+            RunnerUp::Known(..) => RunnerUp::Unknown,
+            runner_up => runner_up,
+        };
+
+        if min_index != index && index < slice.len() {
+            self.update_moved(min, slice, index);
+        } else if min_index == index && min.count > 1 {
+            // equal elements on the other side of the moved minimum may take precedence
+            let equal = match self.tie {
+                TiePolicy::First => {
+                    self.find_equal(&slice[index], slice[..index].iter().enumerate())
+                }
+                TiePolicy::Last => {
+                    let tail = slice[index + 1..].iter().enumerate();
+                    self.find_equal(
+                        &slice[index],
+                        tail.map(|(i, val)| (index + 1 + i, val)).rev(),
+                    )
+                }
+            };
+
+            if let Some(index) = equal {
+                min.state = MinState::Min(index);
+                min.runner_up = RunnerUp::Unknown;
+            }
+        }
+    }
+
+    /// Extends `dirty` to cover the elements of `slice` in `range` before they are modified,
+    /// so that `min` is the min value of the elements outside of `dirty`.
+    ///
+    /// Newly covered elements equal to the min value are no longer counted. If the min value itself
+    /// is covered, it is reset and all elements are marked. `range` may extend beyond `slice`
+    /// to cover elements that are about to be inserted.
+    pub(crate) fn mark_dirty<T>(
+        &self,
+        min: &mut MinTracker,
+        dirty: &mut Option<Range<usize>>,
+        slice: &[T],
+        range: Range<usize>,
+    ) where
+        C: Compare<T>,
+    {
+        let old = dirty.clone().unwrap_or(range.start..range.start);
+        let new = old.start.min(range.start)..old.end.max(range.end);
+
+        let min_index = match min.state {
+            MinState::Min(min_index) if !new.contains(&min_index) => min_index,
+            // an incomparable element may be covered as well
+            _ => {
+                *min = MinTracker::EMPTY;
+                *dirty = Some(0..new.end.max(slice.len()));
+                return;
+            }
+        };
+
+        // the newly covered elements have not been modified yet
+        let lost = if min.count > 1 {
+            let covered = (new.start..old.start).chain(old.end..new.end.min(slice.len()));
+            covered
+                .filter(|&i| self.is_equal(&slice[min_index], &slice[i]))
+                .count()
+        } else {
+            0
+        };
+
+        min.runner_up = match min.runner_up {
+            RunnerUp::Known(r, _) if new.contains(&r) => RunnerUp::Unknown,
+            // the runner-up is equal to the min value
+            RunnerUp::Known(r, count) if min.count > 1 => RunnerUp::Known(r, count - lost),
+            RunnerUp::Known(_, 1) => min.runner_up,
+            RunnerUp::Known(..) => RunnerUp::Unknown,
+            runner_up => runner_up,
+        };
+        min.count -= lost;
+        *dirty = Some(new);
+    }
+
+    /// Updates `min` with the elements of `slice` covered by `dirty`, which is cleared afterwards.
+    pub(crate) fn update_dirty<T>(
+        &self,
+        min: &mut MinTracker,
+        dirty: &mut Option<Range<usize>>,
+        slice: &[T],
+    ) where
+        C: Compare<T>,
+    {
+        let Some(range) = dirty.take() else {
+            return;
+        };
+
+        // elements may have been removed after the range was marked
+        let end = range.end.min(slice.len());
+        let start = range.start.min(end);
+        let modified = slice[start..end].iter().enumerate();
+        let modified = self.slice_min_in(modified.map(|(i, val)| (start + i, val)));
+        *min = self.combine(slice, *min, modified);
+    }
+}
+
+/// The smallest element other than the min value, which is tracked if [Order::track_runner_up] is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunnerUp {
+    /// Not tracked, or invalidated since it was last calculated
+    Unknown,
+    /// There is no comparable element other than the min value
+    Absent,
+    /// The index of the runner-up and the number of elements other than the min value equal to it
+    Known(usize, usize),
+}
+
+/// The state of the min value together with the number of elements equal to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MinTracker {
+    pub(crate) state: MinState,
+    /// Number of elements comparing equal to the min value, including itself
+    pub(crate) count: usize,
+    pub(crate) runner_up: RunnerUp,
+}
+
+impl MinTracker {
+    pub(crate) const EMPTY: Self = Self {
+        state: MinState::Empty,
+        count: 0,
+        runner_up: RunnerUp::Unknown,
+    };
+
+    pub(crate) const INCOMPARABLE: Self = Self {
+        state: MinState::Incomparable,
+        count: 0,
+        runner_up: RunnerUp::Unknown,
+    };
+
+    pub(crate) fn min(index: usize, count: usize) -> Self {
+        Self {
+            state: MinState::Min(index),
+            count,
+            runner_up: RunnerUp::Unknown,
+        }
+    }
+}
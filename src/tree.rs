@@ -0,0 +1,186 @@
+use crate::{MinSmallVec, MinSmallVecError};
+
+/// A two-level tree of [MinSmallVec]s with a known minimum value.
+///
+/// Elements are stored in inner buckets of up to `IS` elements, which are stored in an outer
+/// [MinSmallVec] whose minimum is the bucket with the smallest min value.
+/// Every change to a bucket updates the min value of the bucket and then propagates to the outer
+/// minimum, so only the modified bucket and, if necessary, the outer buckets are compared,
+/// instead of all elements. The outer [MinSmallVec] tracks the runner-up, so when the smallest
+/// bucket is removed or its min value increases, the next smallest bucket is promoted without
+/// scanning all buckets. The runner-up is unknown after a promotion, so the next such change
+/// scans the buckets again.
+///
+/// Elements are addressed by a path of the outer index of the bucket and the inner index within it.
+///
+/// ```rust
+/// # use min_smallvec::MinTree;
+/// let mut tree = MinTree::<u32, 4, 2>::new();
+/// tree.extend([5, 3, 8, 6, 7]);
+/// assert_eq!(tree.get_min(), Some(&3));
+/// assert_eq!(tree.locate_min(), Some((0, 1)));
+/// tree.modify((1, 1), |val| *val = 1);
+/// assert_eq!(tree.locate_min(), Some((1, 1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinTree<T, const OS: usize, const IS: usize> {
+    /// Non-empty buckets, of which only the last one may have less than `IS` elements
+    /// unless elements have been removed
+    buckets: MinSmallVec<MinSmallVec<T, IS>, OS>,
+    len: usize,
+}
+
+impl<T: PartialOrd, const OS: usize, const IS: usize> MinTree<T, OS, IS> {
+    pub fn new() -> Self {
+        let mut buckets = MinSmallVec::new();
+        buckets.set_runner_up_tracking(true);
+        Self { buckets, len: 0 }
+    }
+
+    /// Returns the total number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a slice of all buckets, which are never empty.
+    pub fn buckets(&self) -> &[MinSmallVec<T, IS>] {
+        &self.buckets
+    }
+
+    /// Returns an iterator over references to all elements in the order of their paths.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.iter().flatten()
+    }
+
+    /// Get a reference to the element at `path`, or [None] if it is out of bounds.
+    pub fn get(&self, (outer, inner): (usize, usize)) -> Option<&T> {
+        self.buckets.get(outer)?.get(inner)
+    }
+
+    /// Get a reference to the minimum value.
+    pub fn get_min(&self) -> Option<&T> {
+        self.buckets.get_min()?.get_min()
+    }
+
+    /// Returns the path of the minimum value.
+    pub fn locate_min(&self) -> Option<(usize, usize)> {
+        let (outer, bucket) = self.buckets.get_min_entry()?;
+        Some((outer, bucket.get_min_index()?))
+    }
+
+    /// Pushes a value into the last bucket, or into a new bucket if the last one is full,
+    /// and returns its path.
+    pub fn push(&mut self, value: T) -> (usize, usize) {
+        self.len += 1;
+
+        let last = self.buckets.len().checked_sub(1);
+        if let Some(mut bucket) = last
+            .and_then(|last| self.buckets.get_mut(last))
+            .filter(|bucket| bucket.len() < IS)
+        {
+            bucket.push(value);
+            return (bucket.index, bucket.len() - 1);
+        }
+
+        let mut bucket = MinSmallVec::new();
+        bucket.push(value);
+        self.buckets.push(bucket);
+        (self.buckets.len() - 1, 0)
+    }
+
+    /// Modifies the element at `path`. Its bucket is updated like [MinSmallVec::modify_single],
+    /// after which the outer minimum is updated the same way.
+    ///
+    /// Panics if `path` is out of bounds.
+    /// See [MinTree::try_modify] for a non-panicking version.
+    pub fn modify(&mut self, path: (usize, usize), func: impl FnMut(&mut T)) {
+        if let Err(err) = self.try_modify(path, func) {
+            panic!("{err}");
+        }
+    }
+
+    /// Modifies the element at `path` like [MinTree::modify],
+    /// but returns an error instead of panicking. `func` is not called if an error is returned.
+    pub fn try_modify(
+        &mut self,
+        (outer, inner): (usize, usize),
+        func: impl FnMut(&mut T),
+    ) -> Result<(), MinSmallVecError> {
+        let len = self.buckets.len();
+        let mut bucket = self
+            .buckets
+            .get_mut(outer)
+            .ok_or(MinSmallVecError::OutOfBounds { index: outer, len })?;
+
+        // only mark the bucket as modified if the element exists
+        let inner_len = bucket.len();
+        if inner >= inner_len {
+            return Err(MinSmallVecError::OutOfBounds {
+                index: inner,
+                len: inner_len,
+            });
+        }
+
+        bucket.modify_single(inner, func);
+        Ok(())
+    }
+
+    /// Removes and returns the element at `path`, shifting the elements after it within its bucket
+    /// to the left. If the bucket becomes empty, it is removed as well,
+    /// shifting the following buckets to the left.
+    ///
+    /// Panics if `path` is out of bounds.
+    pub fn remove(&mut self, (outer, inner): (usize, usize)) -> T {
+        let mut bucket = self.buckets.get_mut(outer).expect("index out of bounds");
+        let value = bucket.remove(inner);
+        let is_empty = bucket.is_empty();
+        drop(bucket);
+
+        if is_empty {
+            self.buckets.remove(outer);
+        }
+
+        self.len -= 1;
+        value
+    }
+
+    /// Removes and returns the minimum value like [MinTree::remove].
+    ///
+    /// Returns [None] if there is no minimum value.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.locate_min().map(|path| self.remove(path))
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+}
+
+impl<T: PartialOrd, const OS: usize, const IS: usize> Default for MinTree<T, OS, IS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd, const OS: usize, const IS: usize> Extend<T> for MinTree<T, OS, IS> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: PartialOrd, const OS: usize, const IS: usize> FromIterator<T> for MinTree<T, OS, IS> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}